@@ -24,7 +24,10 @@
 
 mod settings;
 
-pub use settings::{Config, LoggingConfig, SecurityConfig, TimeoutConfig};
+pub use settings::{
+    Config, LimitsConfig, LoggingConfig, ProtocolAllow, RateLimitConfig, RateLimitRule,
+    SanitiserConfig, SanitiserRuleConfig, SecurityConfig, TimeoutConfig, ToolsConfig,
+};
 
 use std::path::{Path, PathBuf};
 
@@ -6,10 +6,46 @@
 //! All error variants that could potentially contain sensitive data
 //! use generic descriptions instead of including the actual values.
 
+use std::fmt::{self, Write};
 use std::path::PathBuf;
 
 use thiserror::Error;
 
+/// A single problem found while validating the configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    /// JSON path of the offending field (e.g., `security.protected_branches[1]`).
+    pub path: String,
+
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl ValidationIssue {
+    /// Creates a new validation issue.
+    #[must_use]
+    pub fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Formats a list of validation issues, one per line.
+fn format_issues(issues: &[ValidationIssue]) -> String {
+    issues.iter().fold(String::new(), |mut output, issue| {
+        let _ = write!(output, "\n  - {issue}");
+        output
+    })
+}
+
 /// Errors that can occur during configuration operations.
 #[derive(Error, Debug)]
 pub enum ConfigError {
@@ -41,10 +77,10 @@ pub enum ConfigError {
     },
 
     /// Configuration validation failed.
-    #[error("configuration validation failed: {message}")]
+    #[error("configuration validation failed:{}", format_issues(issues))]
     ValidationError {
-        /// Description of the validation failure.
-        message: String,
+        /// Every problem found, in the order the fields were checked.
+        issues: Vec<ValidationIssue>,
     },
 }
 
@@ -65,9 +101,23 @@ mod tests {
     #[test]
     fn validation_error_display() {
         let error = ConfigError::ValidationError {
-            message: "invalid setting".to_string(),
+            issues: vec![ValidationIssue::new("logging.level", "invalid setting")],
+        };
+        let msg = error.to_string();
+        assert!(msg.contains("logging.level: invalid setting"));
+    }
+
+    #[test]
+    fn validation_error_lists_every_issue() {
+        let error = ConfigError::ValidationError {
+            issues: vec![
+                ValidationIssue::new("timeouts.request_timeout_secs", "must be greater than zero"),
+                ValidationIssue::new("limits.max_output_bytes", "must be greater than zero"),
+            ],
         };
         let msg = error.to_string();
-        assert!(msg.contains("invalid setting"));
+        assert_eq!(msg.lines().count(), 3);
+        assert!(msg.contains("timeouts.request_timeout_secs"));
+        assert!(msg.contains("limits.max_output_bytes"));
     }
 }
@@ -0,0 +1,344 @@
+//! Destination directories of `git clone`.
+//!
+//! `git clone <url> [<directory>]` accepts any directory, including one
+//! outside the working directory or one that already holds files. The
+//! destination is resolved before git runs, so it can be confined to the
+//! working directory and reported back: the absolute path of the new
+//! checkout is the natural working directory for the next command.
+
+use std::ffi::OsStr;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use super::command::GitCommandError;
+use super::invocation::ParsedInvocation;
+
+/// Returns the directory name git derives from a repository URL when none is
+/// given, or `None` if it cannot derive one.
+///
+/// Follows git's rules: the last path component, without a trailing `/` or
+/// `.git`, with `.git` appended for bare clones (`--bare`, `--mirror`). For
+/// example, `https://github.com/org/repo.git` gives `repo`.
+#[must_use]
+pub fn default_directory(url: &str, bare: bool) -> Option<String> {
+    // Skip the scheme and any user information
+    let mut rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority_end = rest.find(['/', '\\']).unwrap_or(rest.len());
+    if let Some(at) = rest[..authority_end].rfind('@') {
+        rest = &rest[at + 1..];
+    }
+
+    let mut name = rest.trim_end_matches(|c: char| c == '/' || c.is_whitespace());
+    name = name.strip_suffix("/.git").unwrap_or(name);
+    name = name.trim_end_matches('/');
+    name = name.strip_suffix(".git").unwrap_or(name);
+
+    // A bare "host:port" names the host, not a path
+    if !name.contains('/') {
+        if let Some((host, port)) = name.rsplit_once(':') {
+            if port.chars().all(|c| c.is_ascii_digit()) {
+                name = host;
+            }
+        }
+    }
+
+    let name = name.rsplit(['/', ':', '\\']).next().unwrap_or(name).trim();
+    if name.is_empty() || name == "." || name == ".." {
+        return None;
+    }
+
+    Some(if bare {
+        format!("{name}.git")
+    } else {
+        name.to_string()
+    })
+}
+
+/// Resolves and checks the directory a `clone` creates.
+///
+/// The destination is the `<directory>` argument, relative to the working
+/// directory (or the current directory without one), or the name git derives
+/// from the URL. It must resolve, with symlinks followed, to a path inside
+/// the working directory, and must not be an existing non-empty directory or
+/// a file. A `--separate-git-dir` must resolve inside the working directory
+/// too. Returns `Ok(None)` if no repository is given.
+///
+/// # Errors
+///
+/// Returns an error if the working directory cannot be resolved, no
+/// directory name can be derived from the URL, or the destination is not
+/// allowed.
+pub fn clone_target(
+    invocation: &ParsedInvocation,
+    working_dir: Option<&Path>,
+) -> Result<Option<PathBuf>, GitCommandError> {
+    let Some(url) = invocation.remote() else {
+        return Ok(None);
+    };
+
+    let cwd = match working_dir {
+        Some(dir) => dir.to_path_buf(),
+        None => std::env::current_dir().map_err(|_| GitCommandError::InvalidWorkingDirectory {
+            path: PathBuf::from("."),
+        })?,
+    };
+    let resolved_cwd = cwd
+        .canonicalize()
+        .map_err(|_| GitCommandError::InvalidWorkingDirectory { path: cwd.clone() })?;
+
+    let directory = match invocation.target_dir() {
+        Some(directory) => directory.to_string(),
+        None => default_directory(url, invocation.has_flag(&["--bare", "--mirror"])).ok_or_else(
+            || GitCommandError::NoCloneDirectory {
+                url: url.to_string(),
+            },
+        )?,
+    };
+
+    let invalid = |path: PathBuf, reason: &str| GitCommandError::InvalidCloneTarget {
+        path,
+        reason: reason.to_string(),
+    };
+    let target = resolve_path(&resolved_cwd.join(&directory))
+        .map_err(|e| invalid(cwd.join(&directory), &e.to_string()))?;
+
+    if !target.starts_with(&resolved_cwd) {
+        return Err(invalid(target, "it is outside the working directory"));
+    }
+    if target.exists() {
+        if !target.is_dir() {
+            return Err(invalid(target, "it exists and is not a directory"));
+        }
+        let mut entries =
+            std::fs::read_dir(&target).map_err(|e| invalid(target.clone(), &e.to_string()))?;
+        if entries.next().is_some() {
+            return Err(invalid(target, "it is not an empty directory"));
+        }
+    }
+
+    if let Some(git_dir) = invocation.flag_value("--separate-git-dir") {
+        let resolved = resolve_path(&resolved_cwd.join(git_dir))
+            .map_err(|e| invalid(cwd.join(git_dir), &e.to_string()))?;
+        if !resolved.starts_with(&resolved_cwd) {
+            return Err(invalid(
+                resolved,
+                "its git directory is outside the working directory",
+            ));
+        }
+    }
+
+    Ok(Some(target))
+}
+
+/// Resolves a path that may not exist yet.
+///
+/// Components are resolved one at a time: each one that exists is
+/// canonicalised, resolving symlinks, and the rest are appended. A `..`
+/// removes a missing component, or else moves to the parent of the resolved
+/// directory, so later existing components are resolved again (e.g.,
+/// `missing/../link` follows `link`).
+///
+/// # Errors
+///
+/// Returns an error if an existing component cannot be canonicalised (e.g.,
+/// a dangling symlink or a symlink loop).
+pub fn resolve_path(path: &Path) -> io::Result<PathBuf> {
+    let mut resolved = if path.is_absolute() {
+        PathBuf::new()
+    } else {
+        std::env::current_dir()?.canonicalize()?
+    };
+    let mut missing: Vec<&OsStr> = Vec::new();
+
+    for component in path.components() {
+        match component {
+            Component::Prefix(_) => resolved.push(component),
+            Component::RootDir => resolved = resolved.join(component).canonicalize()?,
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if missing.pop().is_none() {
+                    resolved.pop();
+                }
+            }
+            Component::Normal(name) if missing.is_empty() => {
+                let next = resolved.join(name);
+                match next.canonicalize() {
+                    Ok(next) => resolved = next,
+                    Err(e) if e.kind() == io::ErrorKind::NotFound && !next.is_symlink() => {
+                        missing.push(name);
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+            Component::Normal(name) => missing.push(name),
+        }
+    }
+
+    resolved.extend(missing);
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(args: &[&str], cwd: &Path) -> Result<Option<PathBuf>, GitCommandError> {
+        let args: Vec<String> = args.iter().map(ToString::to_string).collect();
+        clone_target(&ParsedInvocation::parse("clone", &args), Some(cwd))
+    }
+
+    #[test]
+    fn default_directory_follows_git() {
+        let cases = [
+            ("https://github.com/org/repo.git", "repo"),
+            ("https://github.com/org/repo/", "repo"),
+            ("https://github.com/org/repo/.git", "repo"),
+            ("git@github.com:org/repo.git", "repo"),
+            ("host:repo", "repo"),
+            ("ssh://user@example.com:2222/srv/project", "project"),
+            ("https://example.com:8443", "example.com"),
+            ("/srv/git/tool.git", "tool"),
+        ];
+        for (url, expected) in cases {
+            assert_eq!(
+                default_directory(url, false).as_deref(),
+                Some(expected),
+                "{url}"
+            );
+        }
+
+        assert_eq!(
+            default_directory("https://github.com/org/repo.git", true).as_deref(),
+            Some("repo.git")
+        );
+        assert_eq!(default_directory("https://", false), None);
+    }
+
+    #[test]
+    fn target_defaults_to_repository_name() {
+        let cwd = tempfile::TempDir::new().unwrap();
+        let resolved_cwd = cwd.path().canonicalize().unwrap();
+
+        assert_eq!(
+            target(&["https://github.com/org/repo.git"], cwd.path()).unwrap(),
+            Some(resolved_cwd.join("repo"))
+        );
+        assert_eq!(
+            target(&["https://github.com/org/repo.git", "a/../b"], cwd.path()).unwrap(),
+            Some(resolved_cwd.join("b"))
+        );
+        assert_eq!(target(&["--depth", "1"], cwd.path()).unwrap(), None);
+    }
+
+    #[test]
+    fn target_must_stay_inside_working_directory() {
+        let cwd = tempfile::TempDir::new().unwrap();
+        let url = "https://github.com/org/repo.git";
+
+        for directory in ["../escape", "/tmp/elsewhere", "sub/../../escape"] {
+            let result = target(&[url, directory], cwd.path());
+            assert!(
+                matches!(result, Err(GitCommandError::InvalidCloneTarget { .. })),
+                "{directory}: {result:?}"
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn target_symlink_out_of_working_directory_is_refused() {
+        let cwd = tempfile::TempDir::new().unwrap();
+        let outside = tempfile::TempDir::new().unwrap();
+        std::os::unix::fs::symlink(outside.path(), cwd.path().join("link")).unwrap();
+
+        let result = target(
+            &["https://github.com/org/repo.git", "link/repo"],
+            cwd.path(),
+        );
+        assert!(matches!(
+            result,
+            Err(GitCommandError::InvalidCloneTarget { .. })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn target_symlink_after_missing_component_is_refused() {
+        let cwd = tempfile::TempDir::new().unwrap();
+        let outside = tempfile::TempDir::new().unwrap();
+        std::os::unix::fs::symlink(outside.path(), cwd.path().join("link")).unwrap();
+
+        let result = target(
+            &["https://github.com/org/repo.git", "missing/../link/repo"],
+            cwd.path(),
+        );
+        assert!(matches!(
+            result,
+            Err(GitCommandError::InvalidCloneTarget { .. })
+        ));
+    }
+
+    #[test]
+    fn separate_git_dir_must_stay_inside_working_directory() {
+        let cwd = tempfile::TempDir::new().unwrap();
+        let url = "https://github.com/org/repo.git";
+
+        for flag in ["--separate-git-dir=../../x", "--separate-git-dir=/tmp/x"] {
+            let error = target(&[flag, url], cwd.path()).unwrap_err();
+            assert!(
+                error.to_string().contains("git directory is outside"),
+                "{flag}: {error}"
+            );
+        }
+        assert!(target(&["--separate-git-dir", "git", url], cwd.path()).is_ok());
+    }
+
+    #[test]
+    fn target_must_not_be_a_non_empty_directory_or_file() {
+        let cwd = tempfile::TempDir::new().unwrap();
+        let url = "https://github.com/org/repo.git";
+        std::fs::create_dir(cwd.path().join("empty")).unwrap();
+        std::fs::create_dir(cwd.path().join("repo")).unwrap();
+        std::fs::write(cwd.path().join("repo").join("README"), "hi").unwrap();
+        std::fs::write(cwd.path().join("file"), "hi").unwrap();
+
+        assert!(target(&[url, "empty"], cwd.path()).is_ok());
+
+        let error = target(&[url], cwd.path()).unwrap_err();
+        assert!(
+            error.to_string().contains("not an empty directory"),
+            "{error}"
+        );
+
+        let error = target(&[url, "file"], cwd.path()).unwrap_err();
+        assert!(error.to_string().contains("not a directory"), "{error}");
+    }
+
+    #[test]
+    fn resolve_path_of_missing_components() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let base = temp.path().canonicalize().unwrap();
+
+        assert_eq!(
+            resolve_path(&temp.path().join("a/b/../c")).unwrap(),
+            base.join("a/c")
+        );
+        assert_eq!(
+            resolve_path(&temp.path().join("a/../..")).unwrap(),
+            base.parent().unwrap()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolve_path_follows_symlinks_after_parent_of_missing_component() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let outside = tempfile::TempDir::new().unwrap();
+        std::os::unix::fs::symlink(outside.path(), temp.path().join("link")).unwrap();
+
+        assert_eq!(
+            resolve_path(&temp.path().join("missing/../link/repo")).unwrap(),
+            outside.path().canonicalize().unwrap().join("repo")
+        );
+    }
+}
@@ -0,0 +1,306 @@
+//! Environment of git child processes.
+//!
+//! Git reads dozens of environment variables, and several of them change
+//! what runs or which repository is used: `GIT_SSH_COMMAND` and
+//! `GIT_ASKPASS` run programs, `GIT_EXEC_PATH` replaces git's own helpers,
+//! `GIT_CONFIG_PARAMETERS` injects configuration and `GIT_DIR` points at
+//! another repository. Git therefore starts from an empty environment: only
+//! an allowlist of variables is inherited from the server (enough to find
+//! programs, the user's configuration, the SSH agent and credential helpers),
+//! and a few values are always forced.
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+
+/// Variables inherited from the server's environment by default.
+///
+/// A trailing `*` matches any suffix (e.g., `LC_*`).
+pub const DEFAULT_INHERITED_VARIABLES: &[&str] = &[
+    // Finding programs and the user's configuration
+    "HOME",
+    "PATH",
+    "USER",
+    "LOGNAME",
+    "SHELL",
+    "TMPDIR",
+    "XDG_CONFIG_HOME",
+    // Locale and time zone, for messages and dates
+    "LANG",
+    "LANGUAGE",
+    "LC_*",
+    "TZ",
+    // SSH agent and credential helpers (credential-cache, libsecret)
+    "SSH_AUTH_SOCK",
+    "SSH_AGENT_PID",
+    "XDG_RUNTIME_DIR",
+    "DBUS_SESSION_BUS_ADDRESS",
+    // Network proxies and certificate stores
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "ALL_PROXY",
+    "NO_PROXY",
+    "http_proxy",
+    "https_proxy",
+    "all_proxy",
+    "no_proxy",
+    "SSL_CERT_FILE",
+    "SSL_CERT_DIR",
+    // Windows essentials, including Git Credential Manager's profile paths
+    "SYSTEMROOT",
+    "WINDIR",
+    "COMSPEC",
+    "PATHEXT",
+    "TEMP",
+    "TMP",
+    "USERPROFILE",
+    "HOMEDRIVE",
+    "HOMEPATH",
+    "APPDATA",
+    "LOCALAPPDATA",
+    "PROGRAMDATA",
+];
+
+/// Variables the server sets itself, which cannot be inherited or overridden.
+pub const RESERVED_VARIABLES: &[&str] = &[
+    "GIT_TERMINAL_PROMPT",
+    "GIT_PROTOCOL_FROM_USER",
+    "GIT_ALLOW_PROTOCOL",
+    "GIT_CONFIG_NOSYSTEM",
+];
+
+/// Returns whether an allowlist entry matches a variable name.
+///
+/// Names are case-insensitive on Windows, like the environment itself.
+fn matches(pattern: &str, name: &str) -> bool {
+    let eq = |a: &str, b: &str| {
+        if cfg!(windows) {
+            a.eq_ignore_ascii_case(b)
+        } else {
+            a == b
+        }
+    };
+    let (compared, expected) = pattern
+        .strip_suffix('*')
+        .map_or((Some(name), pattern), |prefix| {
+            (name.get(..prefix.len()), prefix)
+        });
+    compared.is_some_and(|compared| eq(compared, expected))
+}
+
+/// The environment git runs with.
+#[derive(Debug, Clone)]
+pub struct GitEnvironment {
+    /// Allowlist of inherited variable names (a trailing `*` is a prefix match).
+    inherited: Vec<String>,
+
+    /// Variables set to fixed values, overriding inherited ones.
+    variables: BTreeMap<String, String>,
+
+    /// Whether git ignores the system-wide configuration file.
+    no_system_config: bool,
+}
+
+impl Default for GitEnvironment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GitEnvironment {
+    /// Creates the default environment policy: the
+    /// [default allowlist](DEFAULT_INHERITED_VARIABLES), with the system
+    /// configuration still read.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            inherited: DEFAULT_INHERITED_VARIABLES
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            variables: BTreeMap::new(),
+            no_system_config: false,
+        }
+    }
+
+    /// Also inherits the given variables (a trailing `*` matches any suffix).
+    ///
+    /// [Reserved variables](RESERVED_VARIABLES) are never inherited.
+    #[must_use]
+    pub fn with_inherited(mut self, names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.inherited.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets a variable to a fixed value.
+    ///
+    /// [Reserved variables](RESERVED_VARIABLES) cannot be set and are ignored.
+    #[must_use]
+    pub fn with_variable(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.variables.insert(name.into(), value.into());
+        self
+    }
+
+    /// Makes git ignore the system-wide configuration file
+    /// (`GIT_CONFIG_NOSYSTEM=1`).
+    #[must_use]
+    pub const fn with_no_system_config(mut self, no_system_config: bool) -> Self {
+        self.no_system_config = no_system_config;
+        self
+    }
+
+    /// Returns whether a variable is inherited from the server's environment.
+    #[must_use]
+    pub fn is_inherited(&self, name: &str) -> bool {
+        !Self::is_reserved(name) && self.inherited.iter().any(|pattern| matches(pattern, name))
+    }
+
+    /// Returns whether the server sets a variable itself.
+    #[must_use]
+    pub fn is_reserved(name: &str) -> bool {
+        RESERVED_VARIABLES
+            .iter()
+            .any(|reserved| matches(reserved, name))
+    }
+
+    /// Returns the complete environment for git, built from the server's
+    /// environment.
+    #[must_use]
+    pub fn variables(&self) -> Vec<(OsString, OsString)> {
+        self.variables_from(std::env::vars_os())
+    }
+
+    /// Returns the complete environment for git, built from the given
+    /// inherited environment.
+    ///
+    /// Contains the allowed inherited variables, then the configured values,
+    /// then the forced values: `GIT_TERMINAL_PROMPT=0` (never prompt),
+    /// `GIT_PROTOCOL_FROM_USER=0` (refuse transports git only allows when a
+    /// user asks for them) and, if enabled, `GIT_CONFIG_NOSYSTEM=1`.
+    #[must_use]
+    pub fn variables_from(
+        &self,
+        inherited: impl IntoIterator<Item = (OsString, OsString)>,
+    ) -> Vec<(OsString, OsString)> {
+        let mut variables: Vec<(OsString, OsString)> = inherited
+            .into_iter()
+            .filter(|(name, _)| {
+                name.to_str().is_some_and(|name| {
+                    self.is_inherited(name) && !self.variables.contains_key(name)
+                })
+            })
+            .collect();
+
+        variables.extend(
+            self.variables
+                .iter()
+                .filter(|(name, _)| !Self::is_reserved(name))
+                .map(|(name, value)| (name.into(), value.into())),
+        );
+
+        variables.push(("GIT_TERMINAL_PROMPT".into(), "0".into()));
+        variables.push(("GIT_PROTOCOL_FROM_USER".into(), "0".into()));
+        if self.no_system_config {
+            variables.push(("GIT_CONFIG_NOSYSTEM".into(), "1".into()));
+        }
+        variables
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn environment_of(
+        policy: &GitEnvironment,
+        inherited: &[(&str, &str)],
+    ) -> Vec<(String, String)> {
+        let inherited = inherited
+            .iter()
+            .map(|(name, value)| (OsString::from(name), OsString::from(value)));
+        policy
+            .variables_from(inherited)
+            .into_iter()
+            .map(|(name, value)| (name.into_string().unwrap(), value.into_string().unwrap()))
+            .collect()
+    }
+
+    fn names(variables: &[(String, String)]) -> Vec<&str> {
+        variables.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    #[test]
+    fn only_allowed_variables_are_inherited() {
+        let variables = environment_of(
+            &GitEnvironment::new(),
+            &[
+                ("HOME", "/home/me"),
+                ("PATH", "/usr/bin"),
+                ("LC_ALL", "C"),
+                ("SSH_AUTH_SOCK", "/tmp/agent"),
+                ("GIT_SSH_COMMAND", "evil"),
+                ("GIT_EXEC_PATH", "/tmp/evil"),
+                ("GIT_CONFIG_PARAMETERS", "'core.pager'='evil'"),
+                ("GIT_DIR", "/etc"),
+                ("GIT_ASKPASS", "/tmp/evil"),
+                ("LD_PRELOAD", "/tmp/evil.so"),
+            ],
+        );
+
+        assert_eq!(
+            names(&variables),
+            vec![
+                "HOME",
+                "PATH",
+                "LC_ALL",
+                "SSH_AUTH_SOCK",
+                "GIT_TERMINAL_PROMPT",
+                "GIT_PROTOCOL_FROM_USER"
+            ]
+        );
+    }
+
+    #[test]
+    fn forced_values_cannot_be_overridden() {
+        let policy = GitEnvironment::new()
+            .with_inherited(["GIT_TERMINAL_PROMPT"])
+            .with_variable("GIT_PROTOCOL_FROM_USER", "1");
+        let variables = environment_of(&policy, &[("GIT_TERMINAL_PROMPT", "1")]);
+
+        assert_eq!(
+            variables,
+            vec![
+                ("GIT_TERMINAL_PROMPT".to_string(), "0".to_string()),
+                ("GIT_PROTOCOL_FROM_USER".to_string(), "0".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn configured_extensions() {
+        let policy = GitEnvironment::new()
+            .with_inherited(["GCM_*"])
+            .with_variable("HOME", "/srv/git-home")
+            .with_no_system_config(true);
+        let variables = environment_of(
+            &policy,
+            &[
+                ("HOME", "/home/me"),
+                ("GCM_CREDENTIAL_STORE", "secretservice"),
+            ],
+        );
+
+        assert_eq!(
+            variables,
+            vec![
+                (
+                    "GCM_CREDENTIAL_STORE".to_string(),
+                    "secretservice".to_string()
+                ),
+                ("HOME".to_string(), "/srv/git-home".to_string()),
+                ("GIT_TERMINAL_PROMPT".to_string(), "0".to_string()),
+                ("GIT_PROTOCOL_FROM_USER".to_string(), "0".to_string()),
+                ("GIT_CONFIG_NOSYSTEM".to_string(), "1".to_string()),
+            ]
+        );
+    }
+}
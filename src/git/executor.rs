@@ -6,6 +6,8 @@
 //! 2. Capturing and sanitising output
 //! 3. Detecting Git LFS usage
 //! 4. Enforcing execution timeouts
+//! 5. Reporting progress of long-running commands
+//! 6. Locking repositories against concurrent updates
 //!
 //! # Credential Handling
 //!
@@ -15,15 +17,31 @@
 //! - Credential helpers (macOS Keychain, Windows Credential Manager, libsecret)
 //! - SSH agent for SSH key authentication
 //! - `GIT_TERMINAL_PROMPT=0` prevents interactive credential prompts
-
-use std::process::Stdio;
+//!
+//! Git does not inherit the server's whole environment: see
+//! [`GitEnvironment`] for the variables it runs with. Hooks and configuration
+//! in the repository itself that would run programs are overridden or refuse
+//! the command, as described in [`LocalConfigGuard`].
+
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
 use std::time::Duration;
 
+use tokio::io::AsyncReadExt;
 use tokio::process::Command;
+use tokio::sync::mpsc::UnboundedSender;
 use tokio::time::timeout;
-
-use crate::git::command::GitCommand;
+use tracing::warn;
+
+use crate::git::command::{GitCommand, GitCommandError};
+use crate::git::environment::GitEnvironment;
+use crate::git::local_config::LocalConfigGuard;
+use crate::git::lock::{find_git_dir, LockError, RepositoryLock};
+use crate::git::progress::{collapse_redraws, GitProgress};
+use crate::git::refs::RefFilter;
 use crate::git::sanitiser::OutputSanitiser;
+use crate::git::transport::DEFAULT_ALLOWED_PROTOCOLS;
 
 /// Output from a Git command execution.
 #[derive(Debug, Clone)]
@@ -102,6 +120,9 @@ impl CommandOutput {
 /// Default timeout for git command execution (5 minutes).
 const DEFAULT_TIMEOUT_SECS: u64 = 300;
 
+/// Default time to wait for a repository lock (30 seconds).
+const DEFAULT_LOCK_TIMEOUT_SECS: u64 = 30;
+
 /// Default maximum output size in bytes (10 MiB).
 const DEFAULT_MAX_OUTPUT_BYTES: usize = 10 * 1024 * 1024;
 
@@ -119,6 +140,26 @@ pub struct GitExecutor {
 
     /// Maximum output size in bytes (combined stdout + stderr).
     max_output_bytes: usize,
+
+    /// Time to wait for another operation on the same repository.
+    lock_timeout: Duration,
+
+    /// Transports git may use, passed to it as `GIT_ALLOW_PROTOCOL`.
+    allowed_protocols: Vec<String>,
+
+    /// Environment variables git runs with.
+    environment: GitEnvironment,
+
+    /// Handling of dangerous repository-local hooks and configuration.
+    local_config: LocalConfigGuard,
+}
+
+/// Returns the default allowed transports.
+fn default_allowed_protocols() -> Vec<String> {
+    DEFAULT_ALLOWED_PROTOCOLS
+        .iter()
+        .map(ToString::to_string)
+        .collect()
 }
 
 impl Default for GitExecutor {
@@ -135,6 +176,10 @@ impl GitExecutor {
             sanitiser: OutputSanitiser::new(),
             timeout: Duration::from_secs(DEFAULT_TIMEOUT_SECS),
             max_output_bytes: DEFAULT_MAX_OUTPUT_BYTES,
+            lock_timeout: Duration::from_secs(DEFAULT_LOCK_TIMEOUT_SECS),
+            allowed_protocols: default_allowed_protocols(),
+            environment: GitEnvironment::new(),
+            local_config: LocalConfigGuard::default(),
         }
     }
 
@@ -145,6 +190,10 @@ impl GitExecutor {
             sanitiser: OutputSanitiser::new(),
             timeout,
             max_output_bytes: DEFAULT_MAX_OUTPUT_BYTES,
+            lock_timeout: Duration::from_secs(DEFAULT_LOCK_TIMEOUT_SECS),
+            allowed_protocols: default_allowed_protocols(),
+            environment: GitEnvironment::new(),
+            local_config: LocalConfigGuard::default(),
         }
     }
 
@@ -155,9 +204,52 @@ impl GitExecutor {
             sanitiser: OutputSanitiser::new(),
             timeout,
             max_output_bytes,
+            lock_timeout: Duration::from_secs(DEFAULT_LOCK_TIMEOUT_SECS),
+            allowed_protocols: default_allowed_protocols(),
+            environment: GitEnvironment::new(),
+            local_config: LocalConfigGuard::default(),
         }
     }
 
+    /// Sets how long to wait for another operation on the same repository.
+    #[must_use]
+    pub const fn with_lock_timeout(mut self, lock_timeout: Duration) -> Self {
+        self.lock_timeout = lock_timeout;
+        self
+    }
+
+    /// Sets the transports git may use (e.g., "https", "ssh").
+    ///
+    /// Git refuses any other transport, including for submodules and
+    /// redirects, as a second line of defence behind the transport guard.
+    #[must_use]
+    pub fn with_allowed_protocols(mut self, protocols: Vec<String>) -> Self {
+        self.allowed_protocols = protocols;
+        self
+    }
+
+    /// Sets the environment variables git runs with.
+    #[must_use]
+    pub fn with_environment(mut self, environment: GitEnvironment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Sets how dangerous repository-local hooks and configuration are
+    /// handled.
+    #[must_use]
+    pub fn with_local_config(mut self, local_config: LocalConfigGuard) -> Self {
+        self.local_config = local_config;
+        self
+    }
+
+    /// Replaces the sanitiser applied to command output.
+    #[must_use]
+    pub fn with_sanitiser(mut self, sanitiser: OutputSanitiser) -> Self {
+        self.sanitiser = sanitiser;
+        self
+    }
+
     /// Returns the configured timeout duration.
     #[must_use]
     pub const fn timeout(&self) -> Duration {
@@ -170,6 +262,36 @@ impl GitExecutor {
         self.max_output_bytes
     }
 
+    /// Returns the configured repository lock timeout.
+    #[must_use]
+    pub const fn lock_timeout(&self) -> Duration {
+        self.lock_timeout
+    }
+
+    /// Returns the transports git may use.
+    #[must_use]
+    pub fn allowed_protocols(&self) -> &[String] {
+        &self.allowed_protocols
+    }
+
+    /// Returns the environment policy for git.
+    #[must_use]
+    pub const fn environment(&self) -> &GitEnvironment {
+        &self.environment
+    }
+
+    /// Returns how dangerous repository-local configuration is handled.
+    #[must_use]
+    pub const fn local_config(&self) -> &LocalConfigGuard {
+        &self.local_config
+    }
+
+    /// Returns the sanitiser applied to command output.
+    #[must_use]
+    pub const fn sanitiser(&self) -> &OutputSanitiser {
+        &self.sanitiser
+    }
+
     /// Executes a Git command.
     ///
     /// # Arguments
@@ -185,14 +307,86 @@ impl GitExecutor {
     ///
     /// Returns an error if:
     /// - The working directory does not exist or is not accessible
+    /// - Another operation holds the repository lock for too long
+    /// - A `clone` destination is no longer allowed
+    /// - The repository's own hooks or configuration would run a program
+    ///   and cannot be overridden (or the policy is to reject)
     /// - The Git process fails to start
     /// - The command execution times out
     pub async fn execute(&self, command: &GitCommand) -> Result<CommandOutput, ExecutorError> {
+        self.execute_with_progress(command, None).await
+    }
+
+    /// Executes a Git command, reporting progress while it runs.
+    ///
+    /// If `progress` is given and the command supports it, Git is run with
+    /// `--progress` and each parsed progress update is sent on the channel
+    /// as stderr is read. Progress redraws are collapsed in the returned
+    /// stderr, which is otherwise sanitised and truncated as in
+    /// [`execute`](Self::execute).
+    ///
+    /// Dropping the returned future before it completes kills the Git
+    /// process, which is how cancelled requests are aborted.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`execute`](Self::execute).
+    pub async fn execute_with_progress(
+        &self,
+        command: &GitCommand,
+        progress: Option<UnboundedSender<GitProgress>>,
+    ) -> Result<CommandOutput, ExecutorError> {
+        self.execute_with_filter(command, progress, None).await
+    }
+
+    /// Executes a Git command, keeping only the `ls-remote` lines that pass
+    /// `ref_filter`.
+    ///
+    /// The filter is applied before output size limits, so a filtered listing
+    /// of a repository with many refs is not truncated. Progress is reported
+    /// as in [`execute_with_progress`](Self::execute_with_progress).
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`execute`](Self::execute).
+    pub async fn execute_with_filter(
+        &self,
+        command: &GitCommand,
+        progress: Option<UnboundedSender<GitProgress>>,
+        ref_filter: Option<&RefFilter>,
+    ) -> Result<CommandOutput, ExecutorError> {
         // Validate working directory exists before executing
         if let Some(dir) = command.working_dir() {
             Self::validate_working_directory(dir)?;
         }
 
+        // Keep other server instances from updating the repository at the same time
+        let _lock = self.lock_repository(command).await?;
+
+        // Inspect the repository's own configuration and hooks; a clone
+        // creates the repository, so it gets fixed overrides instead
+        let overrides = if command.command() == "clone" {
+            self.local_config.clone_overrides()
+        } else {
+            let guard = self.local_config.clone();
+            let working_dir = command.working_dir().cloned();
+            let environment = self.environment.clone();
+            tokio::task::spawn_blocking(move || guard.check(working_dir.as_deref(), &environment))
+                .await
+                .map_err(|e| ExecutorError::ProcessError {
+                    message: format!("Failed to inspect the repository configuration: {e}"),
+                })?
+                .map_err(|settings| ExecutorError::UnsafeRepositoryConfig { settings })?
+        };
+
+        // A clone destination may have changed while the command waited to
+        // run (e.g., replaced by a symlink), so check it again just before
+        // git starts and give git the path just checked
+        let mut command = Cow::Borrowed(command);
+        if command.command() == "clone" {
+            command.to_mut().resolve_clone_target()?;
+        }
+
         // Build the command
         let mut cmd = Command::new("git");
 
@@ -201,35 +395,63 @@ impl GitExecutor {
             cmd.current_dir(dir);
         }
 
-        // Add command and arguments
-        cmd.args(command.build_args());
+        // Add command and arguments, asking for progress output when it is wanted.
+        // Git only reports progress to a terminal unless --progress is given.
+        let progress = progress.filter(|_| Self::supports_progress(command.command()));
+        let mut args = command.build_args();
+        if progress.is_some() {
+            args.insert(1, "--progress");
+        }
+        for setting in &overrides {
+            cmd.arg("-c").arg(setting);
+        }
+        cmd.args(args);
 
         // Configure stdio
         cmd.stdin(Stdio::null());
         cmd.stdout(Stdio::piped());
         cmd.stderr(Stdio::piped());
 
-        // Prevent Git from prompting for credentials interactively.
-        // If credentials are not available via credential helpers or SSH agent,
-        // git will fail with an error rather than hanging.
-        cmd.env("GIT_TERMINAL_PROMPT", "0");
+        // Make sure git does not outlive a timed-out or cancelled execution
+        cmd.kill_on_drop(true);
+
+        // Start from an empty environment, so variables such as GIT_SSH_COMMAND
+        // or GIT_DIR in the server's environment cannot change what git does.
+        // This also sets GIT_TERMINAL_PROMPT=0: if credentials are not available
+        // via credential helpers or SSH agent, git fails rather than hanging.
+        cmd.env_clear();
+        cmd.envs(self.environment.variables());
+
+        // Only let git use the allowed transports (e.g., never `ext::`)
+        cmd.env("GIT_ALLOW_PROTOCOL", self.allowed_protocols.join(":"));
 
         // Execute the command with timeout
-        let output = timeout(self.timeout, cmd.output())
-            .await
-            .map_err(|_| ExecutorError::Timeout {
-                timeout_secs: self.timeout.as_secs(),
-            })?
-            .map_err(|e| ExecutorError::ProcessError {
-                message: format!("Failed to execute git: {e}"),
-            })?;
+        let (raw_stdout, raw_stderr, status) =
+            timeout(self.timeout, self.run(cmd, progress.as_ref()))
+                .await
+                .map_err(|_| ExecutorError::Timeout {
+                    timeout_secs: self.timeout.as_secs(),
+                })?
+                .map_err(|e| ExecutorError::ProcessError {
+                    message: format!("Failed to execute git: {e}"),
+                })?;
 
         // Convert output to strings and sanitise
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&raw_stdout);
+        let stderr = collapse_redraws(&String::from_utf8_lossy(&raw_stderr));
 
-        let sanitised_stdout = self.sanitiser.sanitise(&stdout).into_owned();
-        let sanitised_stderr = self.sanitiser.sanitise(&stderr).into_owned();
+        let (sanitised_stdout, mut redacted_by) = self.sanitiser.sanitise_with_report(&stdout);
+        let mut sanitised_stdout = sanitised_stdout.into_owned();
+        if let Some(filter) = ref_filter {
+            sanitised_stdout = filter.retain(&sanitised_stdout);
+        }
+        let (sanitised_stderr, stderr_redacted_by) = self.sanitiser.sanitise_with_report(&stderr);
+        let sanitised_stderr = sanitised_stderr.into_owned();
+        for rule in stderr_redacted_by {
+            if !redacted_by.contains(&rule) {
+                redacted_by.push(rule);
+            }
+        }
 
         // Apply output size limits
         let (final_stdout, stdout_truncated) =
@@ -238,7 +460,7 @@ impl GitExecutor {
         let (final_stderr, stderr_truncated) =
             Self::truncate_output(&sanitised_stderr, remaining_budget);
 
-        let exit_code = output.status.code().unwrap_or(-1);
+        let exit_code = status.code().unwrap_or(-1);
 
         let mut result = CommandOutput::new_with_truncation(
             final_stdout,
@@ -248,6 +470,21 @@ impl GitExecutor {
             stderr_truncated,
         );
 
+        if !redacted_by.is_empty() {
+            let rules = redacted_by.join(", ");
+            warn!(command = command.command(), rules = %rules, "Redacted possible credentials from git output");
+            result.add_warning(format!(
+                "Possible credentials were redacted from the output (rules: {rules})"
+            ));
+        }
+
+        if command.credentials_stripped() {
+            result.add_warning(
+                "Credentials embedded in a URL were removed; authentication uses your Git \
+                 credential helper",
+            );
+        }
+
         // Check for LFS
         if Self::detect_lfs(&result) {
             result.add_warning(
@@ -259,6 +496,117 @@ impl GitExecutor {
         Ok(result)
     }
 
+    /// Returns whether a command updates the repository it runs in.
+    fn updates_repository(command: &str) -> bool {
+        matches!(command, "fetch" | "pull" | "push")
+    }
+
+    /// Takes the lock of the repository a command updates.
+    ///
+    /// Returns `None` for commands that do not update a repository, and for
+    /// directories outside a repository (Git reports those itself). If the
+    /// lock file cannot be created, e.g. in a read-only repository, the
+    /// command runs unlocked.
+    async fn lock_repository(
+        &self,
+        command: &GitCommand,
+    ) -> Result<Option<RepositoryLock>, ExecutorError> {
+        if !Self::updates_repository(command.command()) {
+            return Ok(None);
+        }
+
+        let dir = match command.working_dir() {
+            Some(dir) => dir.clone(),
+            None => match std::env::current_dir() {
+                Ok(dir) => dir,
+                Err(_) => return Ok(None),
+            },
+        };
+        let Some(git_dir) = find_git_dir(&dir) else {
+            return Ok(None);
+        };
+
+        match RepositoryLock::acquire(&git_dir, self.lock_timeout).await {
+            Ok(lock) => Ok(Some(lock)),
+            Err(LockError::Busy { waited, .. }) => Err(ExecutorError::RepositoryBusy {
+                repository: Self::repository_root(&git_dir),
+                waited_secs: waited.as_secs(),
+            }),
+            Err(e @ LockError::Io { .. }) => {
+                tracing::warn!(error = %e, "Running git without a repository lock");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Returns the directory to name in messages about a git dir.
+    fn repository_root(git_dir: &Path) -> PathBuf {
+        if git_dir.file_name().is_some_and(|name| name == ".git") {
+            git_dir.parent().unwrap_or(git_dir).to_path_buf()
+        } else {
+            git_dir.to_path_buf()
+        }
+    }
+
+    /// Returns whether a command accepts the `--progress` flag.
+    fn supports_progress(command: &str) -> bool {
+        matches!(command, "clone" | "fetch" | "pull" | "push")
+    }
+
+    /// Spawns git and collects its output, streaming stderr progress updates.
+    async fn run(
+        &self,
+        mut cmd: Command,
+        progress: Option<&UnboundedSender<GitProgress>>,
+    ) -> std::io::Result<(Vec<u8>, Vec<u8>, ExitStatus)> {
+        let mut child = cmd.spawn()?;
+        let mut stdout_pipe = child.stdout.take().expect("stdout is piped");
+        let mut stderr_pipe = child.stderr.take().expect("stderr is piped");
+
+        let read_stdout = async {
+            let mut stdout = Vec::new();
+            stdout_pipe.read_to_end(&mut stdout).await.map(|_| stdout)
+        };
+
+        let read_stderr = async {
+            let mut stderr = Vec::new();
+            let mut chunk = [0_u8; 4096];
+            // Start of the progress line not yet terminated by '\r' or '\n'
+            let mut line_start = 0;
+
+            loop {
+                let read = stderr_pipe.read(&mut chunk).await?;
+                if read == 0 {
+                    break;
+                }
+                stderr.extend_from_slice(&chunk[..read]);
+
+                let Some(sender) = progress else {
+                    continue;
+                };
+                while let Some(end) = stderr[line_start..]
+                    .iter()
+                    .position(|&b| b == b'\r' || b == b'\n')
+                {
+                    let line = String::from_utf8_lossy(&stderr[line_start..line_start + end]);
+                    if let Some(mut update) = GitProgress::parse(&line) {
+                        update.stage = self.sanitiser.sanitise(&update.stage).into_owned();
+                        // The receiver going away only means nobody is listening any more
+                        let _ = sender.send(update);
+                    }
+                    line_start += end + 1;
+                }
+            }
+
+            Ok::<_, std::io::Error>(stderr)
+        };
+
+        let (stdout, stderr) = tokio::try_join!(read_stdout, read_stderr)?;
+        let status = child.wait().await?;
+
+        Ok((stdout, stderr, status))
+    }
+
     /// Detects if the output indicates Git LFS usage.
     fn detect_lfs(output: &CommandOutput) -> bool {
         let lfs_indicators = [
@@ -355,6 +703,37 @@ pub enum ExecutorError {
         /// Timeout duration in seconds.
         timeout_secs: u64,
     },
+
+    /// Another operation held the repository lock for too long.
+    #[error(
+        "repository is busy: {} is locked by another operation (waited {waited_secs}s)",
+        repository.display()
+    )]
+    RepositoryBusy {
+        /// Repository that is locked.
+        repository: PathBuf,
+        /// How long we waited for the lock, in seconds.
+        waited_secs: u64,
+    },
+
+    /// A `clone` destination is not allowed, when checked again just before
+    /// git starts.
+    #[error("{source}")]
+    CloneTarget {
+        /// Why the destination is not allowed.
+        #[from]
+        source: GitCommandError,
+    },
+
+    /// The repository's own hooks or configuration would run a program.
+    #[error(
+        "repository configuration would run programs: {} (see security.local_config)",
+        settings.join(", ")
+    )]
+    UnsafeRepositoryConfig {
+        /// The dangerous settings and hooks, e.g. "core.fsmonitor".
+        settings: Vec<String>,
+    },
 }
 
 #[cfg(test)]
@@ -463,6 +842,55 @@ mod tests {
         assert!(msg.contains("300 seconds"));
     }
 
+    #[test]
+    fn repository_busy_error_display() {
+        let error = ExecutorError::RepositoryBusy {
+            repository: PathBuf::from("/work/repo"),
+            waited_secs: 30,
+        };
+        assert_eq!(
+            error.to_string(),
+            "repository is busy: /work/repo is locked by another operation (waited 30s)"
+        );
+    }
+
+    #[test]
+    fn executor_with_lock_timeout() {
+        assert_eq!(
+            GitExecutor::default().lock_timeout(),
+            Duration::from_secs(DEFAULT_LOCK_TIMEOUT_SECS)
+        );
+
+        let executor = GitExecutor::new().with_lock_timeout(Duration::from_secs(5));
+        assert_eq!(executor.lock_timeout(), Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn locked_repository_is_reported_busy() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let git_dir = temp.path().join(".git");
+        std::fs::create_dir(&git_dir).unwrap();
+
+        let _held = RepositoryLock::acquire(&git_dir, Duration::ZERO)
+            .await
+            .unwrap();
+
+        let executor = GitExecutor::new().with_lock_timeout(Duration::from_millis(100));
+        let command = GitCommand::new(
+            "fetch",
+            vec!["origin".to_string()],
+            Some(temp.path().to_path_buf()),
+        )
+        .unwrap();
+
+        match executor.execute(&command).await {
+            Err(ExecutorError::RepositoryBusy { repository, .. }) => {
+                assert_eq!(repository, temp.path());
+            }
+            other => panic!("expected RepositoryBusy, got {other:?}"),
+        }
+    }
+
     #[test]
     fn truncate_output_no_truncation() {
         let output = "Hello, world!";
@@ -0,0 +1,633 @@
+//! Argument-aware parsing of git command lines.
+//!
+//! Guards need to know what a command actually does: which remote it talks
+//! to, which refs it updates and which options it uses. Guessing from "the
+//! first argument that does not start with `-`" breaks as soon as an option
+//! takes a value (`git push -o ci.skip origin main`, `git clone --depth 1
+//! URL`). This module parses a command line the way git does, using a table
+//! of the options each allowed command accepts, so every guard sees the same
+//! [`ParsedInvocation`].
+//!
+//! Git accepts several spellings of one option: a short form (`-d`), the
+//! long form (`--delete`) and any unambiguous prefix of the long form
+//! (`--del`). Options are reported by their long form, so a check for
+//! `--delete` covers all of them; options the table does not know, or
+//! ambiguous prefixes, are reported separately.
+
+/// An option given on a command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Flag {
+    /// The option's long form (e.g., "--depth", "--force" for `-f` or
+    /// `--forc`), or the option as written if it is not known.
+    pub name: String,
+    /// The option as written (e.g., "-f", "--forc"), without any value.
+    pub written: String,
+    /// The option's value, if it has one.
+    pub value: Option<String>,
+}
+
+/// A git command line split into its parts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParsedInvocation {
+    /// The remote name or URL the command talks to.
+    remote: Option<String>,
+    /// Refspecs (`push`, `fetch`, `pull`) or ref patterns (`ls-remote`).
+    refspecs: Vec<String>,
+    /// Options, in order. Bundled short options are split (`-fu` → `-f`, `-u`).
+    flags: Vec<Flag>,
+    /// Options the command does not accept, or ambiguous prefixes, as written.
+    unknown_flags: Vec<String>,
+    /// Directory a `clone` creates, if given.
+    target_dir: Option<String>,
+}
+
+/// Whether an option takes a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Value {
+    /// No value.
+    None,
+    /// A value, attached (`--depth=1`, `-j4`) or in the next argument.
+    Required,
+    /// An optional value, only when attached (`--force-with-lease=<ref>`).
+    Optional,
+}
+
+/// An option a command accepts.
+struct GitOption {
+    /// The long form, including the leading `--`.
+    long: &'static str,
+    /// The short form, if any.
+    short: Option<char>,
+    /// Whether the option takes a value.
+    value: Value,
+}
+
+/// A long option without a short form or value.
+const fn flag(long: &'static str) -> GitOption {
+    GitOption {
+        long,
+        short: None,
+        value: Value::None,
+    }
+}
+
+/// A long option taking a value.
+const fn with_value(long: &'static str) -> GitOption {
+    GitOption {
+        long,
+        short: None,
+        value: Value::Required,
+    }
+}
+
+/// A long option with an optional attached value.
+const fn optional_value(long: &'static str) -> GitOption {
+    GitOption {
+        long,
+        short: None,
+        value: Value::Optional,
+    }
+}
+
+impl GitOption {
+    /// Adds a short form.
+    const fn short(mut self, short: char) -> Self {
+        self.short = Some(short);
+        self
+    }
+}
+
+/// Options shared by `clone`, `fetch`, `pull`, `push` and `ls-remote`.
+const COMMON_OPTIONS: &[GitOption] = &[
+    flag("--quiet").short('q'),
+    flag("--verbose").short('v'),
+    flag("--progress"),
+    flag("--no-progress"),
+    flag("--ipv4").short('4'),
+    flag("--ipv6").short('6'),
+];
+
+/// Options of `clone`.
+const CLONE_OPTIONS: &[GitOption] = &[
+    flag("--no-checkout").short('n'),
+    flag("--checkout"),
+    flag("--bare"),
+    flag("--mirror"),
+    flag("--local").short('l'),
+    flag("--no-local"),
+    flag("--no-hardlinks"),
+    flag("--hardlinks"),
+    flag("--shared").short('s'),
+    flag("--dissociate"),
+    flag("--reject-shallow"),
+    flag("--no-reject-shallow"),
+    optional_value("--recurse-submodules"),
+    optional_value("--recursive"),
+    flag("--no-recurse-submodules"),
+    with_value("--jobs").short('j'),
+    with_value("--template"),
+    with_value("--reference"),
+    with_value("--reference-if-able"),
+    with_value("--origin").short('o'),
+    with_value("--branch").short('b'),
+    with_value("--upload-pack").short('u'),
+    with_value("--depth"),
+    with_value("--shallow-since"),
+    with_value("--shallow-exclude"),
+    flag("--single-branch"),
+    flag("--no-single-branch"),
+    flag("--tags"),
+    flag("--no-tags"),
+    flag("--shallow-submodules"),
+    flag("--no-shallow-submodules"),
+    flag("--remote-submodules"),
+    flag("--no-remote-submodules"),
+    with_value("--separate-git-dir"),
+    with_value("--ref-format"),
+    with_value("--config").short('c'),
+    with_value("--server-option"),
+    with_value("--filter"),
+    flag("--also-filter-submodules"),
+    flag("--sparse"),
+    with_value("--bundle-uri"),
+    with_value("--revision"),
+];
+
+/// Options of `fetch`, which `pull` accepts as well.
+const FETCH_OPTIONS: &[GitOption] = &[
+    flag("--all"),
+    flag("--no-all"),
+    flag("--append").short('a'),
+    flag("--atomic"),
+    with_value("--depth"),
+    with_value("--deepen"),
+    with_value("--shallow-since"),
+    with_value("--shallow-exclude"),
+    flag("--unshallow"),
+    flag("--update-shallow"),
+    with_value("--negotiation-tip"),
+    flag("--dry-run"),
+    flag("--force").short('f'),
+    flag("--keep").short('k'),
+    flag("--prefetch"),
+    flag("--prune").short('p'),
+    flag("--no-prune"),
+    flag("--tags").short('t'),
+    flag("--no-tags"),
+    with_value("--jobs").short('j'),
+    flag("--set-upstream"),
+    with_value("--upload-pack"),
+    with_value("--server-option").short('o'),
+    flag("--show-forced-updates"),
+    flag("--no-show-forced-updates"),
+    with_value("--filter"),
+];
+
+/// Options of `fetch` that `pull` does not accept.
+const FETCH_ONLY_OPTIONS: &[GitOption] = &[
+    flag("--multiple").short('m'),
+    flag("--prune-tags").short('P'),
+    flag("--no-tags").short('n'),
+    flag("--refetch"),
+    with_value("--refmap"),
+    flag("--negotiate-only"),
+    flag("--porcelain"),
+    flag("--write-fetch-head"),
+    flag("--no-write-fetch-head"),
+    flag("--auto-maintenance"),
+    flag("--no-auto-maintenance"),
+    flag("--auto-gc"),
+    flag("--no-auto-gc"),
+    flag("--write-commit-graph"),
+    flag("--no-write-commit-graph"),
+    optional_value("--recurse-submodules"),
+    flag("--no-recurse-submodules"),
+    with_value("--recurse-submodules-default"),
+    with_value("--submodule-prefix"),
+    flag("--update-head-ok").short('u'),
+    flag("--stdin"),
+];
+
+/// Merge and rebase options of `pull`.
+const PULL_OPTIONS: &[GitOption] = &[
+    optional_value("--recurse-submodules"),
+    flag("--no-recurse-submodules"),
+    flag("--commit"),
+    flag("--no-commit"),
+    flag("--edit").short('e'),
+    flag("--no-edit"),
+    with_value("--cleanup"),
+    flag("--ff"),
+    flag("--no-ff"),
+    flag("--ff-only"),
+    optional_value("--gpg-sign").short('S'),
+    flag("--no-gpg-sign"),
+    optional_value("--log"),
+    flag("--no-log"),
+    flag("--signoff"),
+    flag("--no-signoff"),
+    flag("--stat"),
+    flag("--no-stat").short('n'),
+    flag("--summary"),
+    flag("--no-summary"),
+    flag("--compact-summary"),
+    flag("--squash"),
+    flag("--no-squash"),
+    flag("--verify"),
+    flag("--no-verify"),
+    with_value("--strategy").short('s'),
+    with_value("--strategy-option").short('X'),
+    flag("--verify-signatures"),
+    flag("--no-verify-signatures"),
+    flag("--autostash"),
+    flag("--no-autostash"),
+    flag("--allow-unrelated-histories"),
+    optional_value("--rebase").short('r'),
+    flag("--no-rebase"),
+];
+
+/// Options of `push`.
+const PUSH_OPTIONS: &[GitOption] = &[
+    flag("--all"),
+    flag("--branches"),
+    flag("--prune"),
+    flag("--mirror"),
+    flag("--dry-run").short('n'),
+    flag("--porcelain"),
+    flag("--delete").short('d'),
+    flag("--tags"),
+    flag("--follow-tags"),
+    flag("--no-follow-tags"),
+    optional_value("--signed"),
+    flag("--no-signed"),
+    flag("--atomic"),
+    flag("--no-atomic"),
+    with_value("--push-option").short('o'),
+    with_value("--receive-pack"),
+    with_value("--exec"),
+    optional_value("--force-with-lease"),
+    flag("--no-force-with-lease"),
+    flag("--force-if-includes"),
+    flag("--no-force-if-includes"),
+    flag("--force").short('f'),
+    with_value("--repo"),
+    flag("--set-upstream").short('u'),
+    flag("--thin"),
+    flag("--no-thin"),
+    with_value("--recurse-submodules"),
+    flag("--no-recurse-submodules"),
+    flag("--verify"),
+    flag("--no-verify"),
+];
+
+/// Options of `ls-remote`.
+const LS_REMOTE_OPTIONS: &[GitOption] = &[
+    flag("--heads"),
+    flag("--branches").short('b'),
+    flag("--tags").short('t'),
+    flag("--refs"),
+    with_value("--upload-pack"),
+    with_value("--exec"),
+    flag("--exit-code"),
+    flag("--get-url"),
+    with_value("--sort"),
+    flag("--symref"),
+    with_value("--server-option").short('o'),
+];
+
+/// Returns the option tables of a command, most specific first.
+fn option_tables(command: &str) -> &'static [&'static [GitOption]] {
+    match command {
+        "clone" => &[CLONE_OPTIONS, COMMON_OPTIONS],
+        "fetch" => &[FETCH_ONLY_OPTIONS, FETCH_OPTIONS, COMMON_OPTIONS],
+        "pull" => &[PULL_OPTIONS, FETCH_OPTIONS, COMMON_OPTIONS],
+        "push" => &[PUSH_OPTIONS, COMMON_OPTIONS],
+        "ls-remote" => &[LS_REMOTE_OPTIONS, COMMON_OPTIONS],
+        _ => &[],
+    }
+}
+
+/// Finds a long option by its name or an unambiguous prefix of it.
+fn find_long(command: &str, name: &str) -> Option<&'static GitOption> {
+    let options = || option_tables(command).iter().flat_map(|table| table.iter());
+    if let Some(exact) = options().find(|option| option.long == name) {
+        return Some(exact);
+    }
+
+    let mut candidates = options().filter(|option| option.long.starts_with(name));
+    let first = candidates.next()?;
+    // The same option may appear in several tables
+    candidates
+        .all(|other| other.long == first.long)
+        .then_some(first)
+}
+
+/// Finds a short option.
+fn find_short(command: &str, short: char) -> Option<&'static GitOption> {
+    option_tables(command)
+        .iter()
+        .flat_map(|table| table.iter())
+        .find(|option| option.short == Some(short))
+}
+
+impl ParsedInvocation {
+    /// Parses the arguments of a git subcommand.
+    ///
+    /// Positional arguments are assigned by command:
+    ///
+    /// - `clone <repository> [<directory>]`
+    /// - `fetch`, `pull`, `push`, `ls-remote`: `[<repository> [<refspec>...]]`
+    ///
+    /// For other commands, all positional arguments are reported as refspecs.
+    /// Everything after `--` is positional. Options are reported by their
+    /// long form; unknown options are reported as written and are assumed not
+    /// to take a separate value.
+    #[must_use]
+    pub fn parse(command: &str, args: &[String]) -> Self {
+        let mut flags = Vec::new();
+        let mut unknown_flags = Vec::new();
+        let mut positionals = Vec::new();
+
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if arg == "--" {
+                positionals.extend(iter.by_ref().cloned());
+                break;
+            }
+
+            if arg.starts_with("--") {
+                let (written, value) = match arg.split_once('=') {
+                    Some((name, value)) => (name, Some(value.to_string())),
+                    None => (arg.as_str(), None),
+                };
+                let Some(option) = find_long(command, written) else {
+                    unknown_flags.push(written.to_string());
+                    flags.push(Flag {
+                        name: written.to_string(),
+                        written: written.to_string(),
+                        value,
+                    });
+                    continue;
+                };
+                let value = if value.is_none() && option.value == Value::Required {
+                    iter.next().cloned()
+                } else {
+                    value
+                };
+                flags.push(Flag {
+                    name: option.long.to_string(),
+                    written: written.to_string(),
+                    value,
+                });
+                continue;
+            }
+
+            if let Some(short) = arg.strip_prefix('-').filter(|s| !s.is_empty()) {
+                // Bundled short options end at the first one taking a value,
+                // which takes the rest of the argument or the next argument
+                for (i, letter) in short.char_indices() {
+                    let rest = &short[i + letter.len_utf8()..];
+                    let written = format!("-{letter}");
+                    let Some(option) = find_short(command, letter) else {
+                        unknown_flags.push(written.clone());
+                        flags.push(Flag {
+                            name: written.clone(),
+                            written,
+                            value: None,
+                        });
+                        continue;
+                    };
+                    let name = option.long.to_string();
+                    let value = match option.value {
+                        Value::None => {
+                            flags.push(Flag {
+                                name,
+                                written,
+                                value: None,
+                            });
+                            continue;
+                        }
+                        Value::Required if rest.is_empty() => iter.next().cloned(),
+                        Value::Optional if rest.is_empty() => None,
+                        Value::Required | Value::Optional => Some(rest.to_string()),
+                    };
+                    flags.push(Flag {
+                        name,
+                        written,
+                        value,
+                    });
+                    break;
+                }
+                continue;
+            }
+
+            positionals.push(arg.clone());
+        }
+
+        let mut parsed = Self {
+            flags,
+            unknown_flags,
+            ..Self::default()
+        };
+
+        let mut positionals = positionals.into_iter();
+        match command {
+            "clone" => {
+                parsed.remote = positionals.next();
+                parsed.target_dir = positionals.next();
+            }
+            "fetch" | "pull" | "push" | "ls-remote" => {
+                parsed.remote = positionals.next();
+                parsed.refspecs = positionals.collect();
+            }
+            _ => parsed.refspecs = positionals.collect(),
+        }
+
+        // `push --repo=<repository>` names the remote instead of a positional
+        if command == "push" && parsed.remote.is_none() {
+            parsed.remote = parsed.flag_value("--repo").map(String::from);
+        }
+
+        parsed
+    }
+
+    /// Returns the remote name or URL the command talks to, if given.
+    #[must_use]
+    pub fn remote(&self) -> Option<&str> {
+        self.remote.as_deref()
+    }
+
+    /// Returns the refspecs or ref patterns.
+    #[must_use]
+    pub fn refspecs(&self) -> &[String] {
+        &self.refspecs
+    }
+
+    /// Returns the options, in order.
+    #[must_use]
+    pub fn flags(&self) -> &[Flag] {
+        &self.flags
+    }
+
+    /// Returns the options the command does not accept, and ambiguous
+    /// prefixes, as written.
+    #[must_use]
+    pub fn unknown_flags(&self) -> &[String] {
+        &self.unknown_flags
+    }
+
+    /// Returns the directory a `clone` creates, if given.
+    #[must_use]
+    pub fn target_dir(&self) -> Option<&str> {
+        self.target_dir.as_deref()
+    }
+
+    /// Returns whether any of the named options was given, by long form.
+    #[must_use]
+    pub fn has_flag(&self, names: &[&str]) -> bool {
+        self.flags
+            .iter()
+            .any(|flag| names.contains(&flag.name.as_str()))
+    }
+
+    /// Returns the value of the last occurrence of an option, if any.
+    #[must_use]
+    pub fn flag_value(&self, name: &str) -> Option<&str> {
+        self.flags
+            .iter()
+            .rev()
+            .find(|flag| flag.name == name)
+            .and_then(|flag| flag.value.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(command: &str, args: &[&str]) -> ParsedInvocation {
+        let args: Vec<String> = args.iter().map(ToString::to_string).collect();
+        ParsedInvocation::parse(command, &args)
+    }
+
+    #[test]
+    fn push_option_value_is_not_the_remote() {
+        let parsed = parse("push", &["-o", "ci.skip", "origin", "main"]);
+
+        assert_eq!(parsed.remote(), Some("origin"));
+        assert_eq!(parsed.refspecs(), &["main"]);
+        assert_eq!(parsed.flag_value("--push-option"), Some("ci.skip"));
+    }
+
+    #[test]
+    fn clone_depth_value_is_not_the_url() {
+        let parsed = parse(
+            "clone",
+            &["--depth", "1", "https://github.com/org/repo.git", "dir"],
+        );
+
+        assert_eq!(parsed.remote(), Some("https://github.com/org/repo.git"));
+        assert_eq!(parsed.target_dir(), Some("dir"));
+        assert_eq!(parsed.flag_value("--depth"), Some("1"));
+    }
+
+    #[test]
+    fn attached_values() {
+        let parsed = parse(
+            "clone",
+            &["--depth=1", "-bdev", "https://example.com/r.git"],
+        );
+
+        assert_eq!(parsed.flag_value("--depth"), Some("1"));
+        assert_eq!(parsed.flag_value("--branch"), Some("dev"));
+        assert_eq!(parsed.remote(), Some("https://example.com/r.git"));
+    }
+
+    #[test]
+    fn bundled_short_flags_are_split() {
+        let parsed = parse("push", &["-fu", "origin", "main"]);
+
+        assert!(parsed.has_flag(&["--force"]));
+        assert!(parsed.has_flag(&["--set-upstream"]));
+        assert_eq!(parsed.remote(), Some("origin"));
+    }
+
+    #[test]
+    fn optional_values_only_attach_with_equals() {
+        let parsed = parse("push", &["--force-with-lease", "origin", "main"]);
+        assert_eq!(parsed.remote(), Some("origin"));
+
+        let parsed = parse("push", &["--force-with-lease=main:abc", "origin", "main"]);
+        assert_eq!(parsed.flag_value("--force-with-lease"), Some("main:abc"));
+        assert_eq!(parsed.refspecs(), &["main"]);
+    }
+
+    #[test]
+    fn double_dash_ends_options() {
+        let parsed = parse("fetch", &["--prune", "--", "origin", "-weird-branch"]);
+
+        assert_eq!(parsed.remote(), Some("origin"));
+        assert_eq!(parsed.refspecs(), &["-weird-branch"]);
+        assert_eq!(parsed.flags().len(), 1);
+    }
+
+    #[test]
+    fn push_repo_option_names_remote() {
+        let parsed = parse("push", &["--repo=https://example.com/r.git", "--all"]);
+        assert_eq!(parsed.remote(), Some("https://example.com/r.git"));
+    }
+
+    #[test]
+    fn ls_remote_patterns() {
+        let parsed = parse("ls-remote", &["--heads", "origin", "main", "dev"]);
+
+        assert_eq!(parsed.remote(), Some("origin"));
+        assert_eq!(parsed.refspecs(), &["main", "dev"]);
+    }
+
+    #[test]
+    fn no_arguments() {
+        let parsed = parse("fetch", &[]);
+
+        assert_eq!(parsed, ParsedInvocation::default());
+    }
+
+    #[test]
+    fn abbreviations_expand_to_the_long_form() {
+        let parsed = parse(
+            "push",
+            &["--delet", "--force-w", "--no-verif", "origin", "main"],
+        );
+        assert!(parsed.has_flag(&["--delete"]));
+        assert!(parsed.has_flag(&["--force-with-lease"]));
+        assert!(parsed.has_flag(&["--no-verify"]));
+        assert_eq!(parsed.flags()[0].written, "--delet");
+        assert_eq!(parsed.remote(), Some("origin"));
+        assert_eq!(parsed.unknown_flags(), Vec::<String>::new());
+
+        let parsed = parse(
+            "clone",
+            &["--upload-pac", "evil", "https://example.com/r.git"],
+        );
+        assert_eq!(parsed.flag_value("--upload-pack"), Some("evil"));
+        assert_eq!(parsed.remote(), Some("https://example.com/r.git"));
+
+        let parsed = parse("fetch", &["--al"]);
+        assert!(parsed.has_flag(&["--all"]));
+    }
+
+    #[test]
+    fn short_options_map_to_the_long_form_per_command() {
+        assert!(parse("clone", &["-c", "a.b=c"]).has_flag(&["--config"]));
+        assert!(parse("fetch", &["-n"]).has_flag(&["--no-tags"]));
+        assert!(parse("pull", &["-n"]).has_flag(&["--no-stat"]));
+        assert!(parse("push", &["-n"]).has_flag(&["--dry-run"]));
+    }
+
+    #[test]
+    fn unknown_and_ambiguous_options_are_reported() {
+        let parsed = parse("push", &["--no-such-option", "--f", "-Z", "origin"]);
+        assert_eq!(parsed.unknown_flags(), &["--no-such-option", "--f", "-Z"]);
+        assert_eq!(parsed.remote(), Some("origin"));
+    }
+}
@@ -0,0 +1,556 @@
+//! Neutralising repository-local hooks and configuration.
+//!
+//! A repository's own `.git/config` and `.git/hooks` come from whoever last
+//! wrote to it: a clone can be followed by an agent editing files, and git
+//! runs what they name. `core.fsmonitor` and hooks run on `pull`,
+//! `core.sshCommand` on every SSH connection, and filter drivers whenever
+//! files are checked out.
+//!
+//! Before a command runs in a repository, its local configuration (including
+//! files pulled in with `include.path`) and hooks directory are inspected.
+//! Depending on the [policy](LocalConfigPolicy), a dangerous setting either
+//! refuses the command or is overridden with `git -c`, which takes precedence
+//! over every configuration file. An overridden key gets the value from the
+//! user's global or system configuration where there is one, so the user's
+//! own setup keeps working, and a harmless value otherwise.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use serde::{Deserialize, Serialize};
+
+use super::environment::GitEnvironment;
+
+/// Hooks path that disables hooks.
+const NO_HOOKS: &str = "/dev/null";
+
+/// How a dangerous key is neutralised.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Neutral {
+    /// Replace the value.
+    Value(&'static str),
+
+    /// Clear the list of values (e.g., credential helpers) with an empty
+    /// value, then add back the user's own.
+    ResetList,
+
+    /// The key cannot be overridden safely; the command is always refused.
+    Refuse,
+}
+
+/// A configuration key that makes git run a program.
+struct DangerousKey {
+    /// Lowercase `section.name` or `section.*.name`, where `*` is any
+    /// subsection.
+    pattern: &'static str,
+
+    /// How the key is neutralised.
+    neutral: Neutral,
+
+    /// Whether only values starting with `!` (a shell command) are dangerous.
+    only_commands: bool,
+}
+
+const fn key(pattern: &'static str, neutral: Neutral) -> DangerousKey {
+    DangerousKey {
+        pattern,
+        neutral,
+        only_commands: false,
+    }
+}
+
+/// Settings every `clone` runs with: the new repository has no
+/// configuration to inspect yet, but hooks installed from a template run
+/// during checkout.
+const CLONE_OVERRIDES: &[(&str, &str)] =
+    &[("core.hookspath", NO_HOOKS), ("core.fsmonitor", "false")];
+
+/// Repository-local keys that run programs during `fetch`, `pull`, `push`
+/// or `ls-remote`.
+const DANGEROUS_KEYS: &[DangerousKey] = &[
+    key("core.hookspath", Neutral::Value(NO_HOOKS)),
+    key("core.fsmonitor", Neutral::Value("false")),
+    key("core.sshcommand", Neutral::Value("ssh")),
+    key("core.askpass", Neutral::Value("")),
+    key("core.alternaterefscommand", Neutral::Value("")),
+    // The first matching proxy wins, so a `-c` override cannot replace it
+    key("core.gitproxy", Neutral::Refuse),
+    key("credential.helper", Neutral::ResetList),
+    key("credential.*.helper", Neutral::ResetList),
+    key("filter.*.clean", Neutral::Value("")),
+    key("filter.*.smudge", Neutral::Value("")),
+    key("filter.*.process", Neutral::Value("")),
+    // An empty driver would silently keep "ours" instead of merging
+    key("merge.*.driver", Neutral::Refuse),
+    key("gpg.program", Neutral::Value("gpg")),
+    key("gpg.openpgp.program", Neutral::Value("gpg")),
+    key("gpg.x509.program", Neutral::Value("gpgsm")),
+    key("gpg.ssh.program", Neutral::Value("ssh-keygen")),
+    key("remote.*.uploadpack", Neutral::Value("git-upload-pack")),
+    key("remote.*.receivepack", Neutral::Value("git-receive-pack")),
+    DangerousKey {
+        pattern: "submodule.*.update",
+        neutral: Neutral::Value("checkout"),
+        only_commands: true,
+    },
+];
+
+/// Splits a key into section, subsection (if any) and name.
+fn split_key(key: &str) -> Option<(&str, Option<&str>, &str)> {
+    let (section, rest) = key.split_once('.')?;
+    Some(match rest.rsplit_once('.') {
+        Some((subsection, name)) => (section, Some(subsection), name),
+        None => (section, None, rest),
+    })
+}
+
+/// Returns whether a key (as git lists it: lowercase section and name,
+/// subsection as written) matches a pattern.
+fn matches(pattern: &str, key: &str) -> bool {
+    let (Some((p_section, p_subsection, p_name)), Some((section, subsection, name))) =
+        (split_key(pattern), split_key(key))
+    else {
+        return false;
+    };
+
+    p_section == section
+        && p_name == name
+        && match (p_subsection, subsection) {
+            (None, None) | (Some("*"), Some(_)) => true,
+            (Some(expected), Some(subsection)) => expected == subsection,
+            _ => false,
+        }
+}
+
+/// Returns the dangerous key a key matches, if any.
+fn dangerous_key(key: &str) -> Option<&'static DangerousKey> {
+    DANGEROUS_KEYS
+        .iter()
+        .find(|dangerous| matches(dangerous.pattern, key))
+}
+
+/// Returns whether a key is one the guard inspects (e.g., "filter.lfs.process").
+#[must_use]
+pub fn is_dangerous_key(key: &str) -> bool {
+    dangerous_key(&normalise_key(key)).is_some()
+}
+
+/// Lowercases the section and name of a key, keeping the subsection.
+fn normalise_key(key: &str) -> String {
+    match split_key(key) {
+        Some((section, Some(subsection), name)) => format!(
+            "{}.{subsection}.{}",
+            section.to_lowercase(),
+            name.to_lowercase()
+        ),
+        _ => key.to_lowercase(),
+    }
+}
+
+/// What to do when a repository's own configuration or hooks would run a
+/// program.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LocalConfigPolicy {
+    /// Override the settings for the command with `git -c`, and refuse it if
+    /// a setting cannot be overridden.
+    #[default]
+    Override,
+
+    /// Refuse the command.
+    Reject,
+}
+
+/// A configuration entry, as listed by `git config --list --show-scope`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigEntry {
+    /// Where the entry comes from ("system", "global", "local", "worktree",
+    /// "command").
+    pub scope: String,
+    /// The key, with lowercase section and name.
+    pub key: String,
+    /// The value; empty for a key given without one.
+    pub value: String,
+}
+
+impl ConfigEntry {
+    /// Returns whether the entry comes from the repository itself.
+    fn is_local(&self) -> bool {
+        matches!(self.scope.as_str(), "local" | "worktree")
+    }
+}
+
+/// Parses the output of `git config --list --show-scope -z`.
+#[must_use]
+pub fn parse_config_list(output: &str) -> Vec<ConfigEntry> {
+    let mut fields = output.split('\0');
+    let mut entries = Vec::new();
+    while let (Some(scope), Some(entry)) = (fields.next(), fields.next()) {
+        let (key, value) = entry.split_once('\n').unwrap_or((entry, ""));
+        entries.push(ConfigEntry {
+            scope: scope.to_string(),
+            key: key.to_string(),
+            value: value.to_string(),
+        });
+    }
+    entries
+}
+
+/// The result of inspecting a repository: what is dangerous, and the `-c`
+/// overrides that neutralise it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LocalConfigReport {
+    /// Dangerous settings and hooks, e.g. "core.fsmonitor" or
+    /// "hook 'post-merge'".
+    pub dangerous: Vec<String>,
+    /// The subset of `dangerous` that cannot be overridden.
+    pub unfixable: Vec<String>,
+    /// `key=value` overrides, in order.
+    pub overrides: Vec<String>,
+}
+
+impl LocalConfigReport {
+    /// Adds an override unless it is already present.
+    fn add_override(&mut self, key: &str, value: &str) {
+        let setting = format!("{key}={value}");
+        if !self.overrides.contains(&setting) {
+            self.overrides.push(setting);
+        }
+    }
+}
+
+/// Inspects repositories for local hooks and configuration that run programs.
+#[derive(Debug, Clone, Default)]
+pub struct LocalConfigGuard {
+    /// What to do with dangerous settings.
+    policy: LocalConfigPolicy,
+
+    /// Keys the repository may set (normalised). Allowing "core.hookspath"
+    /// also allows the hooks directory.
+    allowed_keys: Vec<String>,
+}
+
+impl LocalConfigGuard {
+    /// Creates a guard with the given policy and no allowed keys.
+    #[must_use]
+    pub const fn new(policy: LocalConfigPolicy) -> Self {
+        Self {
+            policy,
+            allowed_keys: Vec::new(),
+        }
+    }
+
+    /// Lets repositories set the given keys (e.g., "filter.lfs.process").
+    #[must_use]
+    pub fn with_allowed_keys(mut self, keys: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        self.allowed_keys
+            .extend(keys.into_iter().map(|key| normalise_key(key.as_ref())));
+        self
+    }
+
+    /// Returns the configured policy.
+    #[must_use]
+    pub const fn policy(&self) -> LocalConfigPolicy {
+        self.policy
+    }
+
+    /// Returns whether a repository may set a key.
+    fn is_allowed(&self, key: &str) -> bool {
+        self.allowed_keys.iter().any(|allowed| allowed == key)
+    }
+
+    /// Finds the dangerous settings among configuration entries and the
+    /// names of the hooks in the repository's hooks directory.
+    #[must_use]
+    pub fn report(&self, entries: &[ConfigEntry], hooks: &[String]) -> LocalConfigReport {
+        let mut report = LocalConfigReport::default();
+
+        for entry in entries.iter().filter(|entry| entry.is_local()) {
+            let Some(dangerous) = dangerous_key(&entry.key) else {
+                continue;
+            };
+            if self.is_allowed(&entry.key)
+                || (dangerous.only_commands && !entry.value.starts_with('!'))
+                || report.dangerous.contains(&entry.key)
+            {
+                continue;
+            }
+            report.dangerous.push(entry.key.clone());
+
+            // The user's own values, in the order git reads them
+            let own: Vec<&str> = entries
+                .iter()
+                .filter(|other| !other.is_local() && other.key == entry.key)
+                .map(|other| other.value.as_str())
+                .collect();
+
+            match dangerous.neutral {
+                // `git -c` cannot express a key containing '='
+                _ if entry.key.contains('=') => report.unfixable.push(entry.key.clone()),
+                Neutral::Refuse => report.unfixable.push(entry.key.clone()),
+                Neutral::ResetList => {
+                    report.add_override(&entry.key, "");
+                    for value in own {
+                        report.overrides.push(format!("{}={value}", entry.key));
+                    }
+                }
+                Neutral::Value(neutral) => {
+                    let value = own.last().copied().unwrap_or(neutral);
+                    report.add_override(&entry.key, value);
+                }
+            }
+        }
+
+        // Hooks in the repository only run if the user has no hooks path
+        let user_hooks_path = entries
+            .iter()
+            .any(|entry| !entry.is_local() && entry.key == "core.hookspath");
+        if !user_hooks_path && !self.is_allowed("core.hookspath") {
+            for hook in hooks {
+                report.dangerous.push(format!("hook '{hook}'"));
+            }
+            if !hooks.is_empty() {
+                report.add_override("core.hookspath", NO_HOOKS);
+            }
+        }
+
+        report
+    }
+
+    /// Returns the `key=value` settings to pass to `git clone` with `-c`.
+    ///
+    /// Hooks and the file system monitor are disabled, whatever the policy,
+    /// unless their keys are allowed.
+    #[must_use]
+    pub fn clone_overrides(&self) -> Vec<String> {
+        CLONE_OVERRIDES
+            .iter()
+            .filter(|(key, _)| !self.is_allowed(key))
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect()
+    }
+
+    /// Inspects the repository a command runs in and returns the `key=value`
+    /// settings to pass to git with `-c`.
+    ///
+    /// Outside a repository there is nothing to inspect and nothing is
+    /// returned. Git is run to read the configuration, so this blocks; the
+    /// executor calls it off the async runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns the dangerous settings that refuse the command: all of them
+    /// with the reject policy, otherwise those that cannot be overridden.
+    pub fn check(
+        &self,
+        working_dir: Option<&Path>,
+        environment: &GitEnvironment,
+    ) -> Result<Vec<String>, Vec<String>> {
+        let variables = environment.variables();
+        let git = |args: &'static [&'static str]| {
+            let mut cmd = Command::new("git");
+            if let Some(dir) = working_dir {
+                cmd.current_dir(dir);
+            }
+            let output = cmd
+                .args(args)
+                .env_clear()
+                .envs(variables.iter().map(|(name, value)| (name, value)))
+                .stdin(Stdio::null())
+                .stderr(Stdio::null())
+                .output();
+            match output {
+                Ok(output) if output.status.success() => {
+                    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+                }
+                _ => None,
+            }
+        };
+
+        let Some(git_dir) = git(&["rev-parse", "--git-common-dir"]) else {
+            return Ok(Vec::new());
+        };
+        let config =
+            git(&["config", "--list", "--show-scope", "--includes", "-z"]).unwrap_or_default();
+
+        let hooks_dir = working_dir
+            .map_or_else(PathBuf::new, Path::to_path_buf)
+            .join(git_dir.trim_end())
+            .join("hooks");
+        let report = self.report(&parse_config_list(&config), &list_hooks(&hooks_dir));
+
+        match self.policy {
+            _ if report.dangerous.is_empty() => Ok(Vec::new()),
+            LocalConfigPolicy::Reject => Err(report.dangerous),
+            LocalConfigPolicy::Override if !report.unfixable.is_empty() => Err(report.unfixable),
+            LocalConfigPolicy::Override => Ok(report.overrides),
+        }
+    }
+}
+
+/// Returns the names of the hooks in a hooks directory, skipping the
+/// `.sample` files git installs.
+fn list_hooks(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut hooks: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| !name.ends_with(".sample"))
+        .collect();
+    hooks.sort();
+    hooks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(scope: &str, key: &str, value: &str) -> ConfigEntry {
+        ConfigEntry {
+            scope: scope.to_string(),
+            key: key.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            .stdout(Stdio::null())
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?}");
+    }
+
+    #[test]
+    fn parse_config_list_output() {
+        let output =
+            "system\0credential.helper\nstore\0local\0core.bare\nfalse\0local\0filter.X.required\0";
+        assert_eq!(
+            parse_config_list(output),
+            vec![
+                entry("system", "credential.helper", "store"),
+                entry("local", "core.bare", "false"),
+                entry("local", "filter.X.required", ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn dangerous_keys_match_subsections() {
+        assert!(is_dangerous_key("core.hooksPath"));
+        assert!(is_dangerous_key("filter.lfs.process"));
+        assert!(is_dangerous_key("credential.https://example.com.helper"));
+        assert!(is_dangerous_key("gpg.ssh.program"));
+        assert!(!is_dangerous_key("gpg.other.program"));
+        assert!(!is_dangerous_key("filter.process"));
+        assert!(!is_dangerous_key("core.bare"));
+    }
+
+    #[test]
+    fn overrides_restore_the_users_own_values() {
+        let entries = [
+            entry("global", "core.sshcommand", "ssh -i ~/.ssh/work"),
+            entry("global", "credential.helper", "osxkeychain"),
+            entry("local", "core.sshcommand", "evil"),
+            entry("local", "core.fsmonitor", "evil"),
+            entry("local", "credential.helper", "evil"),
+            entry("local", "core.bare", "false"),
+            entry("local", "submodule.lib.update", "rebase"),
+            entry("local", "submodule.evil.update", "!evil"),
+        ];
+        let report = LocalConfigGuard::default().report(&entries, &[]);
+
+        assert_eq!(
+            report.dangerous,
+            [
+                "core.sshcommand",
+                "core.fsmonitor",
+                "credential.helper",
+                "submodule.evil.update"
+            ]
+        );
+        assert_eq!(report.unfixable, Vec::<String>::new());
+        assert_eq!(
+            report.overrides,
+            [
+                "core.sshcommand=ssh -i ~/.ssh/work",
+                "core.fsmonitor=false",
+                "credential.helper=",
+                "credential.helper=osxkeychain",
+                "submodule.evil.update=checkout"
+            ]
+        );
+    }
+
+    #[test]
+    fn hooks_are_disabled_unless_the_user_has_a_hooks_path() {
+        let guard = LocalConfigGuard::default();
+        let hooks = ["post-merge".to_string()];
+
+        let report = guard.report(&[], &hooks);
+        assert_eq!(report.dangerous, ["hook 'post-merge'"]);
+        assert_eq!(report.overrides, ["core.hookspath=/dev/null"]);
+
+        let report = guard.report(&[entry("global", "core.hookspath", "~/.hooks")], &hooks);
+        assert_eq!(report, LocalConfigReport::default());
+    }
+
+    #[test]
+    fn allowed_and_unfixable_keys() {
+        let entries = [
+            entry("local", "filter.lfs.process", "git-lfs filter-process"),
+            entry("local", "merge.ours.driver", "evil"),
+        ];
+        let guard = LocalConfigGuard::default().with_allowed_keys(["filter.lfs.process"]);
+        let report = guard.report(&entries, &[]);
+
+        assert_eq!(report.dangerous, ["merge.ours.driver"]);
+        assert_eq!(report.unfixable, ["merge.ours.driver"]);
+    }
+
+    #[test]
+    fn clone_overrides_skip_allowed_keys() {
+        assert_eq!(
+            LocalConfigGuard::new(LocalConfigPolicy::Reject).clone_overrides(),
+            ["core.hookspath=/dev/null", "core.fsmonitor=false"]
+        );
+        assert_eq!(
+            LocalConfigGuard::default()
+                .with_allowed_keys(["core.hooksPath"])
+                .clone_overrides(),
+            ["core.fsmonitor=false"]
+        );
+    }
+
+    #[test]
+    fn check_neutralises_a_repository() {
+        let repo = tempfile::TempDir::new().unwrap();
+        git(repo.path(), &["init", "-q"]);
+        git(repo.path(), &["config", "core.fsmonitor", "evil"]);
+        std::fs::write(repo.path().join(".git/hooks/post-merge"), "#!/bin/sh\n").unwrap();
+        let environment = GitEnvironment::new().with_no_system_config(true);
+
+        let overrides = LocalConfigGuard::default()
+            .check(Some(repo.path()), &environment)
+            .unwrap();
+        assert!(overrides.contains(&"core.fsmonitor=false".to_string()));
+
+        let refused = LocalConfigGuard::new(LocalConfigPolicy::Reject)
+            .check(Some(repo.path()), &environment)
+            .unwrap_err();
+        assert!(refused.contains(&"core.fsmonitor".to_string()));
+        assert!(refused.contains(&"hook 'post-merge'".to_string()));
+
+        let outside = tempfile::TempDir::new().unwrap();
+        assert_eq!(
+            LocalConfigGuard::new(LocalConfigPolicy::Reject)
+                .check(Some(outside.path()), &environment),
+            Ok(Vec::new())
+        );
+    }
+}
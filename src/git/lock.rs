@@ -0,0 +1,202 @@
+//! Advisory locks on repositories.
+//!
+//! Several server instances (e.g., one per editor window) may work on the
+//! same checkout. Without coordination, two of them running `pull` at once
+//! race on Git's own `index.lock` and one fails with a confusing error.
+//!
+//! Before running a command that updates a repository, the executor takes an
+//! exclusive advisory lock on a file inside the repository's git dir. Other
+//! instances wait for the lock for a bounded time and then give up with a
+//! clear "repository busy" error. The lock is released when the
+//! [`RepositoryLock`] is dropped, or by the operating system if the process
+//! dies.
+
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use fs4::fs_std::FileExt;
+
+/// Name of the lock file created inside the git dir.
+pub const LOCK_FILE_NAME: &str = "git-proxy-mcp.lock";
+
+/// Interval between attempts to take a contended lock.
+const RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// An exclusive advisory lock on a repository, released when dropped.
+#[derive(Debug)]
+pub struct RepositoryLock {
+    /// Open handle holding the lock.
+    _file: File,
+
+    /// Path of the lock file.
+    path: PathBuf,
+}
+
+impl RepositoryLock {
+    /// Takes the lock of the repository with the given git dir.
+    ///
+    /// Waits up to `wait` for another holder to release it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lock file cannot be opened or locked, or
+    /// [`LockError::Busy`] if the lock is still held after `wait`.
+    pub async fn acquire(git_dir: &Path, wait: Duration) -> Result<Self, LockError> {
+        let path = git_dir.join(LOCK_FILE_NAME);
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(|source| LockError::Io {
+                path: path.clone(),
+                source,
+            })?;
+
+        let started = Instant::now();
+        loop {
+            match file.try_lock_exclusive() {
+                Ok(true) => return Ok(Self { _file: file, path }),
+                Ok(false) => {}
+                Err(source) => return Err(LockError::Io { path, source }),
+            }
+
+            let waited = started.elapsed();
+            if waited >= wait {
+                return Err(LockError::Busy { path, waited });
+            }
+            tokio::time::sleep(RETRY_INTERVAL.min(wait.saturating_sub(waited))).await;
+        }
+    }
+
+    /// Returns the path of the lock file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Finds the git dir of the repository containing `dir`.
+///
+/// Walks up from `dir` looking for a `.git` directory, or a `.git` file
+/// pointing elsewhere (as used by worktrees and submodules). A directory that
+/// is itself a bare repository is its own git dir.
+///
+/// Returns `None` if `dir` is not inside a repository.
+#[must_use]
+pub fn find_git_dir(dir: &Path) -> Option<PathBuf> {
+    for candidate in dir.ancestors() {
+        let dot_git = candidate.join(".git");
+        if dot_git.is_dir() {
+            return Some(dot_git);
+        }
+        if dot_git.is_file() {
+            return read_gitdir_file(&dot_git, candidate);
+        }
+        if is_bare_repository(candidate) {
+            return Some(candidate.to_path_buf());
+        }
+    }
+    None
+}
+
+/// Reads the target of a `.git` file (`gitdir: <path>`).
+fn read_gitdir_file(dot_git: &Path, base: &Path) -> Option<PathBuf> {
+    let contents = std::fs::read_to_string(dot_git).ok()?;
+    let target = contents.trim().strip_prefix("gitdir:")?.trim();
+    let target = base.join(target);
+    target.is_dir().then_some(target)
+}
+
+/// Returns whether `dir` looks like a bare repository.
+fn is_bare_repository(dir: &Path) -> bool {
+    dir.join("HEAD").is_file() && dir.join("objects").is_dir() && dir.join("refs").is_dir()
+}
+
+/// Errors that can occur while taking a repository lock.
+#[derive(Debug, thiserror::Error)]
+pub enum LockError {
+    /// The lock is held by another operation.
+    #[error("lock {} is held by another operation", path.display())]
+    Busy {
+        /// Path of the lock file.
+        path: PathBuf,
+        /// How long we waited for the lock.
+        waited: Duration,
+    },
+
+    /// The lock file could not be opened or locked.
+    #[error("cannot lock {}: {source}", path.display())]
+    Io {
+        /// Path of the lock file.
+        path: PathBuf,
+        /// Underlying error.
+        source: io::Error,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn init_repo(dir: &Path) -> PathBuf {
+        let git_dir = dir.join(".git");
+        std::fs::create_dir_all(git_dir.join("objects")).unwrap();
+        std::fs::create_dir_all(git_dir.join("refs")).unwrap();
+        std::fs::write(git_dir.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        git_dir
+    }
+
+    #[test]
+    fn find_git_dir_from_subdirectory() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let git_dir = init_repo(temp.path());
+        let nested = temp.path().join("src").join("deep");
+        std::fs::create_dir_all(&nested).unwrap();
+
+        assert_eq!(find_git_dir(&nested), Some(git_dir));
+    }
+
+    #[test]
+    fn find_git_dir_follows_gitdir_file() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let real = temp.path().join("real");
+        std::fs::create_dir_all(&real).unwrap();
+        let worktree = temp.path().join("worktree");
+        std::fs::create_dir_all(&worktree).unwrap();
+        std::fs::write(worktree.join(".git"), "gitdir: ../real\n").unwrap();
+
+        assert_eq!(find_git_dir(&worktree), Some(worktree.join("../real")));
+    }
+
+    #[test]
+    fn find_git_dir_of_bare_repository() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let bare = init_repo(temp.path());
+
+        assert_eq!(find_git_dir(&bare), Some(bare));
+    }
+
+    #[tokio::test]
+    async fn second_lock_waits_and_reports_busy() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let git_dir = init_repo(temp.path());
+
+        let first = RepositoryLock::acquire(&git_dir, Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert_eq!(first.path(), git_dir.join(LOCK_FILE_NAME));
+
+        let result = RepositoryLock::acquire(&git_dir, Duration::from_millis(150)).await;
+        assert!(
+            matches!(result, Err(LockError::Busy { waited, .. }) if waited >= Duration::from_millis(150))
+        );
+
+        drop(first);
+        assert!(RepositoryLock::acquire(&git_dir, Duration::ZERO)
+            .await
+            .is_ok());
+    }
+}
@@ -65,10 +65,26 @@
 //! - `ls-remote` — List remote refs
 //! - `rev-parse` — Parse revision
 
+pub mod clone;
 pub mod command;
+pub mod environment;
 pub mod executor;
+pub mod invocation;
+pub mod local_config;
+pub mod lock;
+pub mod progress;
+pub mod refs;
+pub mod remotes;
 pub mod sanitiser;
+pub mod transport;
 
-pub use command::{GitCommand, GitCommandError};
+pub use command::{GitCommand, GitCommandError, UrlCredentialPolicy};
+pub use environment::GitEnvironment;
 pub use executor::{CommandOutput, GitExecutor};
-pub use sanitiser::OutputSanitiser;
+pub use invocation::{Flag, ParsedInvocation};
+pub use local_config::{LocalConfigGuard, LocalConfigPolicy};
+pub use progress::GitProgress;
+pub use refs::{RefFilter, RefKind, RemoteRef};
+pub use remotes::{resolve_remote_urls, RemoteUrl};
+pub use sanitiser::{EntropyCheck, OutputSanitiser, SanitiserRule};
+pub use transport::{transport_of, DEFAULT_ALLOWED_PROTOCOLS};
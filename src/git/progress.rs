@@ -0,0 +1,168 @@
+//! Parsing of Git progress output.
+//!
+//! When run with `--progress`, Git reports long-running phases on stderr as
+//! lines such as:
+//!
+//! ```text
+//! remote: Counting objects: 100% (42/42), done.
+//! Receiving objects:  42% (420/1000), 1.20 MiB | 1.10 MiB/s
+//! Resolving deltas:  10% (3/30)
+//! ```
+//!
+//! Updates within a phase are separated by carriage returns, so a terminal
+//! redraws the same line. This module extracts the phase, percentage and
+//! object counts from such lines, and collapses the redraws so the final
+//! stderr reads the same as it would on a terminal.
+
+/// A single progress update reported by Git.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitProgress {
+    /// The phase being reported (e.g., "Receiving objects").
+    pub stage: String,
+
+    /// Completion of the phase, from 0 to 100.
+    pub percent: u8,
+
+    /// Number of items processed so far, if reported.
+    pub current: Option<u64>,
+
+    /// Total number of items in the phase, if reported.
+    pub total: Option<u64>,
+}
+
+impl GitProgress {
+    /// Parses a single progress line.
+    ///
+    /// Returns `None` if the line does not contain a percentage update.
+    #[must_use]
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        let line = line.strip_prefix("remote:").map_or(line, str::trim_start);
+
+        let percent_at = line.find('%')?;
+        let (head, tail) = line.split_at(percent_at);
+
+        // "<stage>: <percent>"
+        let (stage, percent) = head.rsplit_once(':')?;
+        let stage = stage.trim();
+        let percent: u8 = percent.trim().parse().ok()?;
+        if stage.is_empty() || percent > 100 {
+            return None;
+        }
+
+        // Optional " (<current>/<total>)" after the percentage
+        let counts = tail[1..]
+            .trim_start()
+            .strip_prefix('(')
+            .and_then(|rest| rest.split_once(')'))
+            .and_then(|(counts, _)| counts.split_once('/'))
+            .and_then(|(current, total)| {
+                Some((current.trim().parse().ok()?, total.trim().parse().ok()?))
+            });
+
+        Some(Self {
+            stage: stage.to_string(),
+            percent,
+            current: counts.map(|(current, _)| current),
+            total: counts.map(|(_, total)| total),
+        })
+    }
+
+    /// Returns a short human-readable description of this update.
+    #[must_use]
+    pub fn message(&self) -> String {
+        match (self.current, self.total) {
+            (Some(current), Some(total)) => {
+                format!("{}: {}% ({current}/{total})", self.stage, self.percent)
+            }
+            _ => format!("{}: {}%", self.stage, self.percent),
+        }
+    }
+}
+
+/// Collapses carriage-return redraws, keeping what a terminal would show.
+///
+/// For each line, only the text after the last non-trailing `\r` is kept.
+#[must_use]
+pub fn collapse_redraws(output: &str) -> String {
+    if !output.contains('\r') {
+        return output.to_string();
+    }
+
+    let mut result = String::with_capacity(output.len());
+    for (i, line) in output.split('\n').enumerate() {
+        if i > 0 {
+            result.push('\n');
+        }
+        let visible = line
+            .trim_end_matches('\r')
+            .rsplit('\r')
+            .next()
+            .unwrap_or_default();
+        result.push_str(visible);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_receiving_objects() {
+        let progress =
+            GitProgress::parse("Receiving objects:  42% (420/1000), 1.20 MiB | 1.10 MiB/s")
+                .unwrap();
+
+        assert_eq!(progress.stage, "Receiving objects");
+        assert_eq!(progress.percent, 42);
+        assert_eq!(progress.current, Some(420));
+        assert_eq!(progress.total, Some(1000));
+        assert_eq!(progress.message(), "Receiving objects: 42% (420/1000)");
+    }
+
+    #[test]
+    fn parse_remote_stage() {
+        let progress = GitProgress::parse("remote: Counting objects: 100% (5/5), done.").unwrap();
+
+        assert_eq!(progress.stage, "Counting objects");
+        assert_eq!(progress.percent, 100);
+    }
+
+    #[test]
+    fn parse_without_counts() {
+        let progress = GitProgress::parse("Checking connectivity: 7%").unwrap();
+
+        assert_eq!(progress.percent, 7);
+        assert_eq!(progress.current, None);
+        assert_eq!(progress.message(), "Checking connectivity: 7%");
+    }
+
+    #[test]
+    fn parse_rejects_non_progress_lines() {
+        assert!(GitProgress::parse("Cloning into 'repo'...").is_none());
+        assert!(GitProgress::parse("fatal: repository not found").is_none());
+        assert!(GitProgress::parse("100% done").is_none());
+        assert!(GitProgress::parse("Weird: 250%").is_none());
+        assert!(GitProgress::parse("").is_none());
+    }
+
+    #[test]
+    fn collapse_redraws_keeps_final_update() {
+        let raw = "Cloning into 'repo'...\n\
+                   Receiving objects:  50% (1/2)\rReceiving objects: 100% (2/2), done.\n\
+                   Resolving deltas:   0% (0/1)\rResolving deltas: 100% (1/1), done.\r\n";
+
+        assert_eq!(
+            collapse_redraws(raw),
+            "Cloning into 'repo'...\n\
+             Receiving objects: 100% (2/2), done.\n\
+             Resolving deltas: 100% (1/1), done.\n"
+        );
+    }
+
+    #[test]
+    fn collapse_redraws_without_carriage_returns() {
+        assert_eq!(collapse_redraws("a\nb\n"), "a\nb\n");
+    }
+}
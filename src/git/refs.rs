@@ -0,0 +1,298 @@
+//! Parsing of `git ls-remote` output.
+//!
+//! `ls-remote` prints one `<sha>\t<ref>` line per reference. With `--symref`
+//! it also prints `ref: <target>\t<name>` lines for symbolic refs such as
+//! `HEAD`, and annotated tags are followed by a peeled `<ref>^{}` line giving
+//! the commit the tag points to.
+//!
+//! This module turns that output into [`RemoteRef`]s, and provides a
+//! [`RefFilter`] that is applied before output size limits, so listing a few
+//! refs of a repository with tens of thousands of tags is not truncated.
+
+use glob::Pattern;
+use serde::{Deserialize, Serialize};
+
+/// Suffix of peeled tag lines.
+const PEELED_SUFFIX: &str = "^{}";
+
+/// Kind of a remote reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RefKind {
+    /// A branch (`refs/heads/...`).
+    Branch,
+    /// A tag (`refs/tags/...`).
+    Tag,
+    /// The commit an annotated tag points to (`refs/tags/...^{}`).
+    PeeledTag,
+    /// The remote's `HEAD`.
+    Head,
+    /// A pull or merge request (`refs/pull/...`, `refs/merge-requests/...`).
+    Pull,
+    /// Any other reference (e.g., `refs/notes/...`).
+    Other,
+}
+
+impl RefKind {
+    /// Classifies a reference by name, after any peeled suffix was removed.
+    fn of(name: &str, peeled: bool) -> Self {
+        if name == "HEAD" {
+            Self::Head
+        } else if name.starts_with("refs/heads/") {
+            Self::Branch
+        } else if name.starts_with("refs/tags/") {
+            if peeled {
+                Self::PeeledTag
+            } else {
+                Self::Tag
+            }
+        } else if name.starts_with("refs/pull/") || name.starts_with("refs/merge-requests/") {
+            Self::Pull
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// A reference listed by `git ls-remote`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RemoteRef {
+    /// Object ID the reference points to.
+    pub sha: String,
+
+    /// Full reference name (e.g., "refs/heads/main"), without any `^{}`.
+    #[serde(rename = "ref")]
+    pub name: String,
+
+    /// Kind of reference.
+    pub kind: RefKind,
+
+    /// Target of a symbolic reference (e.g., `HEAD` → "refs/heads/main"),
+    /// if `--symref` was given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+}
+
+/// Parses `git ls-remote` output.
+///
+/// Lines that are not references (e.g., a truncated last line) are skipped.
+#[must_use]
+pub fn parse_ls_remote(output: &str) -> Vec<RemoteRef> {
+    let mut refs: Vec<RemoteRef> = Vec::new();
+    let mut symrefs: Vec<(&str, &str)> = Vec::new();
+
+    for line in output.lines() {
+        let Some((left, name)) = line.split_once('\t') else {
+            continue;
+        };
+
+        if let Some(target) = left.strip_prefix("ref: ") {
+            symrefs.push((name, target));
+            continue;
+        }
+
+        if left.is_empty() || !left.bytes().all(|b| b.is_ascii_hexdigit()) {
+            continue;
+        }
+
+        let (name, peeled) = name
+            .strip_suffix(PEELED_SUFFIX)
+            .map_or((name, false), |name| (name, true));
+        refs.push(RemoteRef {
+            sha: left.to_string(),
+            name: name.to_string(),
+            kind: RefKind::of(name, peeled),
+            target: None,
+        });
+    }
+
+    for (name, target) in symrefs {
+        if let Some(symref) = refs.iter_mut().find(|r| r.name == name) {
+            symref.target = Some(target.to_string());
+        }
+    }
+
+    refs
+}
+
+/// Selects which references of an `ls-remote` listing are kept.
+///
+/// A reference is kept if it matches any of the patterns (or there are none)
+/// and is of one of the kinds (or none are given). Patterns are globs matched
+/// against the full name and the short name, so `release/*` matches
+/// `refs/heads/release/1.0`. Asking for tags also keeps peeled tags.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RefFilter {
+    /// Glob patterns on reference names.
+    patterns: Vec<Pattern>,
+
+    /// Kinds of reference to keep.
+    kinds: Vec<RefKind>,
+}
+
+impl RefFilter {
+    /// Creates a filter from glob patterns and kinds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first invalid pattern.
+    pub fn new(patterns: &[String], kinds: Vec<RefKind>) -> Result<Self, glob::PatternError> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| Pattern::new(pattern))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { patterns, kinds })
+    }
+
+    /// Returns whether the filter keeps every reference.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty() && self.kinds.is_empty()
+    }
+
+    /// Returns whether a reference is kept.
+    #[must_use]
+    pub fn matches(&self, name: &str, kind: RefKind) -> bool {
+        let kind_matches = self.kinds.is_empty()
+            || self.kinds.contains(&kind)
+            || (kind == RefKind::PeeledTag && self.kinds.contains(&RefKind::Tag));
+        if !kind_matches {
+            return false;
+        }
+
+        let short = name
+            .strip_prefix("refs/heads/")
+            .or_else(|| name.strip_prefix("refs/tags/"))
+            .unwrap_or(name);
+        self.patterns.is_empty()
+            || self
+                .patterns
+                .iter()
+                .any(|pattern| pattern.matches(name) || pattern.matches(short))
+    }
+
+    /// Removes the lines of references that are not kept from `ls-remote`
+    /// output.
+    ///
+    /// Symbolic reference lines are kept with the reference they describe.
+    #[must_use]
+    pub fn retain(&self, output: &str) -> String {
+        if self.is_empty() {
+            return output.to_string();
+        }
+
+        let mut kept = String::new();
+        for line in output.lines() {
+            let keep = line.split_once('\t').is_some_and(|(left, name)| {
+                let (name, peeled) = name
+                    .strip_suffix(PEELED_SUFFIX)
+                    .map_or((name, false), |name| (name, true));
+                let kind = if left.starts_with("ref: ") {
+                    RefKind::of(name, false)
+                } else {
+                    RefKind::of(name, peeled)
+                };
+                self.matches(name, kind)
+            });
+            if keep {
+                kept.push_str(line);
+                kept.push('\n');
+            }
+        }
+        kept
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LISTING: &str = "\
+ref: refs/heads/main\tHEAD
+1111111111111111111111111111111111111111\tHEAD
+1111111111111111111111111111111111111111\trefs/heads/main
+2222222222222222222222222222222222222222\trefs/heads/release/1.0
+3333333333333333333333333333333333333333\trefs/tags/v1.0
+4444444444444444444444444444444444444444\trefs/tags/v1.0^{}
+5555555555555555555555555555555555555555\trefs/pull/7/head
+6666666666666666666666666666666666666666\trefs/notes/commits
+";
+
+    #[test]
+    fn parse_classifies_refs() {
+        let refs = parse_ls_remote(LISTING);
+        let kinds: Vec<(&str, RefKind)> = refs.iter().map(|r| (r.name.as_str(), r.kind)).collect();
+
+        assert_eq!(
+            kinds,
+            vec![
+                ("HEAD", RefKind::Head),
+                ("refs/heads/main", RefKind::Branch),
+                ("refs/heads/release/1.0", RefKind::Branch),
+                ("refs/tags/v1.0", RefKind::Tag),
+                ("refs/tags/v1.0", RefKind::PeeledTag),
+                ("refs/pull/7/head", RefKind::Pull),
+                ("refs/notes/commits", RefKind::Other),
+            ]
+        );
+        assert_eq!(refs[4].sha, "4444444444444444444444444444444444444444");
+    }
+
+    #[test]
+    fn parse_resolves_head_symref() {
+        let refs = parse_ls_remote(LISTING);
+
+        assert_eq!(refs[0].target.as_deref(), Some("refs/heads/main"));
+        assert!(refs[1..].iter().all(|r| r.target.is_none()));
+    }
+
+    #[test]
+    fn parse_skips_malformed_lines() {
+        let refs = parse_ls_remote("warning: redirecting\nabc\tHEAD\n12345");
+
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].sha, "abc");
+    }
+
+    #[test]
+    fn serialises_with_ref_and_kebab_case_kind() {
+        let value = serde_json::to_value(parse_ls_remote(LISTING)).unwrap();
+
+        assert_eq!(value[0]["ref"], "HEAD");
+        assert_eq!(value[0]["target"], "refs/heads/main");
+        assert_eq!(value[4]["kind"], "peeled-tag");
+        assert!(value[1].get("target").is_none());
+    }
+
+    #[test]
+    fn filter_by_pattern_matches_short_names() {
+        let filter = RefFilter::new(&["release/*".to_string()], Vec::new()).unwrap();
+        let refs = parse_ls_remote(&filter.retain(LISTING));
+
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].name, "refs/heads/release/1.0");
+    }
+
+    #[test]
+    fn filter_by_kind_keeps_peeled_tags_and_symrefs() {
+        let tags = RefFilter::new(&[], vec![RefKind::Tag]).unwrap();
+        assert_eq!(parse_ls_remote(&tags.retain(LISTING)).len(), 2);
+
+        let head = RefFilter::new(&[], vec![RefKind::Head]).unwrap();
+        let refs = parse_ls_remote(&head.retain(LISTING));
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].target.as_deref(), Some("refs/heads/main"));
+    }
+
+    #[test]
+    fn empty_filter_keeps_everything() {
+        let filter = RefFilter::default();
+        assert!(filter.is_empty());
+        assert_eq!(filter.retain(LISTING), LISTING);
+    }
+
+    #[test]
+    fn invalid_pattern_is_rejected() {
+        assert!(RefFilter::new(&["[".to_string()], Vec::new()).is_err());
+    }
+}
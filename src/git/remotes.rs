@@ -0,0 +1,418 @@
+//! Resolution of the URLs a command will contact.
+//!
+//! A command can name a remote ("origin") instead of giving a URL, or give no
+//! remote at all, and git's configuration can change the URL that is used:
+//! `remote.<name>.pushurl` replaces it for pushes, and
+//! `url.<base>.insteadOf` / `url.<base>.pushInsteadOf` rewrite matching
+//! prefixes. A policy checked against the command line alone is therefore
+//! easily bypassed. This module asks git which URLs a command will actually
+//! use, reading the configuration of the command's working directory.
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use super::command::GitCommand;
+use super::environment::GitEnvironment;
+use super::invocation::ParsedInvocation;
+use super::transport::is_remote_name;
+
+/// Remote git uses when none is given and none is configured for the branch.
+const DEFAULT_REMOTE: &str = "origin";
+
+/// A URL a command will contact.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteUrl {
+    /// The remote as given on the command line, or the one git picks by default.
+    pub remote: String,
+    /// The URL git will use, after `pushurl` and `insteadOf` rewrites.
+    pub url: String,
+}
+
+/// Returns the URLs a command will contact.
+///
+/// `push` uses a remote's push URLs; `clone`, `fetch`, `pull` and
+/// `ls-remote` use its fetch URLs. Without a remote argument, the current
+/// branch's remote (or "origin") is resolved, and `fetch --all`,
+/// `fetch --multiple` and remote groups resolve every remote they name.
+///
+/// A name that is not a configured remote resolves to itself, as git then
+/// treats it as a local path. Other commands contact no remote and return an
+/// empty list.
+///
+/// Git runs with the given environment, as the command itself will.
+#[must_use]
+pub fn resolve_remote_urls(command: &GitCommand, environment: &GitEnvironment) -> Vec<RemoteUrl> {
+    let git = LocalGit {
+        dir: command.working_dir().map(PathBuf::as_path),
+        environment: environment.variables(),
+    };
+    let push = command.command() == "push";
+    let invocation = ParsedInvocation::parse(command.command(), command.args());
+
+    let remotes = match command.command() {
+        "clone" | "ls-remote" | "pull" | "push" => vec![invocation
+            .remote()
+            .map_or_else(|| default_remote(&git, push), String::from)],
+        "fetch" => fetch_remotes(&git, &invocation),
+        _ => return Vec::new(),
+    };
+
+    remotes
+        .into_iter()
+        .flat_map(|remote| {
+            urls_of(&git, &remote, push)
+                .into_iter()
+                .map(move |url| RemoteUrl {
+                    remote: remote.clone(),
+                    url,
+                })
+        })
+        .collect()
+}
+
+/// Returns the remotes a `fetch` contacts, with groups expanded.
+fn fetch_remotes(git: &LocalGit<'_>, invocation: &ParsedInvocation) -> Vec<String> {
+    if invocation.has_flag(&["--all"]) {
+        return git.lines(&["remote"]);
+    }
+
+    let named: Vec<String> = if invocation.has_flag(&["--multiple"]) {
+        invocation
+            .remote()
+            .map(String::from)
+            .into_iter()
+            .chain(invocation.refspecs().iter().cloned())
+            .collect()
+    } else {
+        vec![invocation
+            .remote()
+            .map_or_else(|| default_remote(git, false), String::from)]
+    };
+
+    named
+        .into_iter()
+        .flat_map(|name| {
+            let group = remote_group(git, &name);
+            if group.is_empty() {
+                vec![name]
+            } else {
+                group
+            }
+        })
+        .collect()
+}
+
+/// Returns the remotes listed in `remotes.<name>`, if the name is a group.
+fn remote_group(git: &LocalGit<'_>, name: &str) -> Vec<String> {
+    if !is_remote_name(name) {
+        return Vec::new();
+    }
+
+    git.lines(&["config", "--get-all", &format!("remotes.{name}")])
+        .iter()
+        .flat_map(|line| line.split_whitespace().map(String::from))
+        .collect()
+}
+
+/// Returns the remote git uses when none is given.
+///
+/// Pushes prefer `branch.<name>.pushRemote` and then `remote.pushDefault`;
+/// both fall back to `branch.<name>.remote` and finally "origin".
+fn default_remote(git: &LocalGit<'_>, push: bool) -> String {
+    let branch = git
+        .lines(&["symbolic-ref", "-q", "--short", "HEAD"])
+        .into_iter()
+        .next();
+
+    let mut keys = Vec::new();
+    if let Some(branch) = &branch {
+        if push {
+            keys.push(format!("branch.{branch}.pushRemote"));
+        }
+    }
+    if push {
+        keys.push("remote.pushDefault".to_string());
+    }
+    if let Some(branch) = &branch {
+        keys.push(format!("branch.{branch}.remote"));
+    }
+
+    keys.iter()
+        .find_map(|key| git.lines(&["config", "--get", key]).into_iter().next())
+        .unwrap_or_else(|| DEFAULT_REMOTE.to_string())
+}
+
+/// Returns the URLs git uses for a remote name or URL.
+fn urls_of(git: &LocalGit<'_>, remote: &str, push: bool) -> Vec<String> {
+    if is_remote_name(remote) {
+        let mut args = vec!["remote", "get-url", "--all"];
+        if push {
+            args.push("--push");
+        }
+        args.push(remote);
+        let urls = git.lines(&args);
+        // Not a configured remote: it may still be an `insteadOf` alias
+        if !urls.is_empty() {
+            return urls;
+        }
+    }
+
+    if push {
+        if let Some(rewritten) = push_instead_of(git, remote) {
+            return vec![rewritten];
+        }
+    }
+
+    // `ls-remote --get-url` applies `insteadOf` and works outside a repository
+    let rewritten = git.lines(&["ls-remote", "--get-url", remote]);
+    if rewritten.is_empty() {
+        vec![remote.to_string()]
+    } else {
+        rewritten
+    }
+}
+
+/// Applies the longest matching `url.<base>.pushInsteadOf` rewrite to a URL.
+fn push_instead_of(git: &LocalGit<'_>, url: &str) -> Option<String> {
+    git.lines(&["config", "--get-regexp", r"^url\..*\.pushinsteadof$"])
+        .iter()
+        .filter_map(|line| {
+            let (key, prefix) = line.split_once(' ')?;
+            let base = key.strip_prefix("url.")?.strip_suffix(".pushinsteadof")?;
+            url.strip_prefix(prefix)
+                .map(|rest| (prefix.len(), format!("{base}{rest}")))
+        })
+        .max_by_key(|&(len, _)| len)
+        .map(|(_, rewritten)| rewritten)
+}
+
+/// Runs local git queries in a command's working directory.
+struct LocalGit<'a> {
+    dir: Option<&'a Path>,
+    environment: Vec<(OsString, OsString)>,
+}
+
+impl LocalGit<'_> {
+    /// Runs a git query and returns its non-empty output lines.
+    ///
+    /// Returns an empty list if git fails, e.g., for an unknown remote.
+    fn lines(&self, args: &[&str]) -> Vec<String> {
+        let mut cmd = Command::new("git");
+        if let Some(dir) = self.dir {
+            cmd.current_dir(dir);
+        }
+        let output = cmd
+            .args(args)
+            .env_clear()
+            .envs(self.environment.iter().map(|(name, value)| (name, value)))
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output();
+
+        match output {
+            Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(String::from)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            .stdout(Stdio::null())
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?}");
+    }
+
+    fn repo_with_origin() -> tempfile::TempDir {
+        let temp = tempfile::TempDir::new().unwrap();
+        git(temp.path(), &["init", "-q", "-b", "main"]);
+        git(
+            temp.path(),
+            &["remote", "add", "origin", "https://github.com/org/repo.git"],
+        );
+        temp
+    }
+
+    fn resolve(dir: &Path, command: &str, args: &[&str]) -> Vec<String> {
+        let args = args.iter().map(ToString::to_string).collect();
+        let command = GitCommand::new(command, args, Some(dir.to_path_buf())).unwrap();
+        resolve_remote_urls(&command, &GitEnvironment::new())
+            .into_iter()
+            .map(|remote_url| remote_url.url)
+            .collect()
+    }
+
+    #[test]
+    fn named_remote_resolves_to_its_url() {
+        let repo = repo_with_origin();
+
+        assert_eq!(
+            resolve(repo.path(), "fetch", &["origin"]),
+            ["https://github.com/org/repo.git"]
+        );
+        assert_eq!(
+            resolve(repo.path(), "push", &["origin", "main"]),
+            ["https://github.com/org/repo.git"]
+        );
+    }
+
+    #[test]
+    fn push_uses_pushurl() {
+        let repo = repo_with_origin();
+        git(
+            repo.path(),
+            &[
+                "config",
+                "remote.origin.pushurl",
+                "https://evil.example/x.git",
+            ],
+        );
+
+        assert_eq!(
+            resolve(repo.path(), "push", &["origin", "main"]),
+            ["https://evil.example/x.git"]
+        );
+        assert_eq!(
+            resolve(repo.path(), "pull", &["origin"]),
+            ["https://github.com/org/repo.git"]
+        );
+    }
+
+    #[test]
+    fn instead_of_rewrites_urls_and_aliases() {
+        let repo = repo_with_origin();
+        git(
+            repo.path(),
+            &[
+                "config",
+                "url.https://evil.example/.insteadOf",
+                "https://github.com/",
+            ],
+        );
+        git(
+            repo.path(),
+            &["config", "url.https://gitlab.com/.insteadOf", "gl:"],
+        );
+
+        assert_eq!(
+            resolve(repo.path(), "fetch", &["origin"]),
+            ["https://evil.example/org/repo.git"]
+        );
+        assert_eq!(
+            resolve(repo.path(), "ls-remote", &["https://github.com/a/b.git"]),
+            ["https://evil.example/a/b.git"]
+        );
+        assert_eq!(
+            resolve(repo.path(), "fetch", &["gl:group/project"]),
+            ["https://gitlab.com/group/project"]
+        );
+    }
+
+    #[test]
+    fn push_instead_of_rewrites_push_urls() {
+        let repo = repo_with_origin();
+        git(
+            repo.path(),
+            &[
+                "config",
+                "url.https://evil.example/.pushInsteadOf",
+                "https://github.com/",
+            ],
+        );
+
+        assert_eq!(
+            resolve(repo.path(), "push", &["origin", "main"]),
+            ["https://evil.example/org/repo.git"]
+        );
+        assert_eq!(
+            resolve(repo.path(), "push", &["https://github.com/a/b.git", "main"]),
+            ["https://evil.example/a/b.git"]
+        );
+        assert_eq!(
+            resolve(repo.path(), "fetch", &["origin"]),
+            ["https://github.com/org/repo.git"]
+        );
+    }
+
+    #[test]
+    fn missing_remote_uses_branch_configuration() {
+        let repo = repo_with_origin();
+        git(
+            repo.path(),
+            &[
+                "remote",
+                "add",
+                "upstream",
+                "https://github.com/up/repo.git",
+            ],
+        );
+        git(
+            repo.path(),
+            &["remote", "add", "fork", "https://github.com/me/repo.git"],
+        );
+
+        assert_eq!(
+            resolve(repo.path(), "push", &[]),
+            ["https://github.com/org/repo.git"]
+        );
+
+        git(repo.path(), &["config", "branch.main.remote", "upstream"]);
+        git(repo.path(), &["config", "remote.pushDefault", "fork"]);
+
+        assert_eq!(
+            resolve(repo.path(), "fetch", &[]),
+            ["https://github.com/up/repo.git"]
+        );
+        assert_eq!(
+            resolve(repo.path(), "push", &[]),
+            ["https://github.com/me/repo.git"]
+        );
+    }
+
+    #[test]
+    fn fetch_all_and_groups_resolve_every_remote() {
+        let repo = repo_with_origin();
+        git(
+            repo.path(),
+            &["remote", "add", "other", "https://example.com/other.git"],
+        );
+        git(
+            repo.path(),
+            &["config", "remotes.everything", "origin other"],
+        );
+
+        let expected = [
+            "https://github.com/org/repo.git",
+            "https://example.com/other.git",
+        ];
+        assert_eq!(resolve(repo.path(), "fetch", &["--all"]), expected);
+        assert_eq!(resolve(repo.path(), "fetch", &["everything"]), expected);
+        assert_eq!(
+            resolve(repo.path(), "fetch", &["--multiple", "origin", "other"]),
+            expected
+        );
+    }
+
+    #[test]
+    fn unknown_remote_resolves_to_itself() {
+        let repo = repo_with_origin();
+
+        assert_eq!(
+            resolve(repo.path(), "fetch", &["nonexistent"]),
+            ["nonexistent"]
+        );
+    }
+}
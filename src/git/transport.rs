@@ -0,0 +1,145 @@
+//! Classification of remote URLs by transport.
+//!
+//! Git picks a transport from the form of a URL, and some transports do far
+//! more than fetch objects: `ext::<command>` runs an arbitrary command,
+//! `fd::` talks to inherited file descriptors and `file://` (or a bare path)
+//! reads any repository on disk. This module names the transport a URL would
+//! use, with the names git's `protocol.allow` settings and
+//! `GIT_ALLOW_PROTOCOL` use, so a policy can be checked before git runs.
+
+/// Transports allowed when no protocols are configured.
+pub const DEFAULT_ALLOWED_PROTOCOLS: &[&str] = &["https", "ssh"];
+
+/// Returns whether a character may appear in a URL scheme or remote helper
+/// name.
+const fn is_scheme_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')
+}
+
+/// Returns whether an argument names a configured remote (e.g., "origin")
+/// rather than a URL or path.
+///
+/// Names cannot contain `/` or `:`; `.` and `..` are always paths.
+#[must_use]
+pub fn is_remote_name(remote: &str) -> bool {
+    !remote.is_empty() && !remote.contains(['/', '\\', ':']) && remote != "." && remote != ".."
+}
+
+/// Returns the transport git would use for a URL, or `None` for a remote name.
+///
+/// Follows git's rules:
+///
+/// - `<helper>::<address>` uses the remote helper (e.g., `ext`, `fd`)
+/// - `<scheme>://...` uses the scheme; `git+ssh` and `ssh+git` are `ssh`
+/// - `[user@]host:path` (scp-like syntax) is `ssh`
+/// - anything else, including a Windows drive path, is a local path: `file`
+#[must_use]
+pub fn transport_of(url: &str) -> Option<String> {
+    if is_remote_name(url) {
+        return None;
+    }
+
+    let scheme_len = url
+        .char_indices()
+        .find(|&(_, c)| !is_scheme_char(c))
+        .map_or(url.len(), |(i, _)| i);
+    let (scheme, rest) = url.split_at(scheme_len);
+    let scheme = scheme.to_ascii_lowercase();
+
+    if !scheme.is_empty() && rest.starts_with("::") {
+        return Some(scheme);
+    }
+
+    if scheme.starts_with(|c: char| c.is_ascii_alphabetic()) && rest.starts_with("://") {
+        return Some(match scheme.as_str() {
+            "git+ssh" | "ssh+git" => "ssh".to_string(),
+            _ => scheme,
+        });
+    }
+
+    // A colon before any slash makes it scp-like, unless it is a drive letter
+    let is_drive_path = url.len() >= 2
+        && url.as_bytes()[0].is_ascii_alphabetic()
+        && url.as_bytes()[1] == b':'
+        && (url.len() == 2 || matches!(url.as_bytes()[2], b'/' | b'\\'));
+    match (url.find(':'), url.find('/')) {
+        (Some(colon), slash) if !is_drive_path && slash.map_or(true, |slash| colon < slash) => {
+            Some("ssh".to_string())
+        }
+        _ => Some("file".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_schemes() {
+        assert_eq!(
+            transport_of("https://github.com/org/repo.git").as_deref(),
+            Some("https")
+        );
+        assert_eq!(
+            transport_of("HTTP://example.com/repo").as_deref(),
+            Some("http")
+        );
+        assert_eq!(
+            transport_of("git://example.com/repo").as_deref(),
+            Some("git")
+        );
+        assert_eq!(
+            transport_of("file:///srv/repo.git").as_deref(),
+            Some("file")
+        );
+    }
+
+    #[test]
+    fn ssh_forms() {
+        for url in [
+            "ssh://git@github.com/org/repo.git",
+            "git+ssh://host/repo",
+            "ssh+git://host/repo",
+            "git@github.com:org/repo.git",
+            "host:repo",
+        ] {
+            assert_eq!(transport_of(url).as_deref(), Some("ssh"), "{url}");
+        }
+    }
+
+    #[test]
+    fn remote_helpers() {
+        assert_eq!(
+            transport_of("ext::sh -c touch% /tmp/pwned").as_deref(),
+            Some("ext")
+        );
+        assert_eq!(transport_of("fd::3,4").as_deref(), Some("fd"));
+        assert_eq!(
+            transport_of("persistent-https::example.com/repo").as_deref(),
+            Some("persistent-https")
+        );
+    }
+
+    #[test]
+    fn local_paths() {
+        for path in [
+            "/etc",
+            "./repo",
+            "../repo",
+            ".",
+            "..",
+            "dir/with:colon",
+            "C:\\Users\\me\\repo",
+            "C:/repo",
+        ] {
+            assert_eq!(transport_of(path).as_deref(), Some("file"), "{path}");
+        }
+    }
+
+    #[test]
+    fn remote_names_have_no_transport() {
+        assert_eq!(transport_of("origin"), None);
+        assert_eq!(transport_of("upstream-fork.2"), None);
+        assert!(!is_remote_name("./origin"));
+    }
+}
@@ -5,16 +5,19 @@
 //! The MCP server does not store credentials — it relies on credential
 //! helpers and SSH agent already configured on the user's machine.
 
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use tracing::{error, info, Level};
 use tracing_subscriber::EnvFilter;
 
 use git_proxy_mcp::config;
 use git_proxy_mcp::git::executor::GitExecutor;
+use git_proxy_mcp::git::sanitiser::OutputSanitiser;
 use git_proxy_mcp::mcp::server::{McpServer, SecurityConfig};
-use git_proxy_mcp::security::{AuditEvent, AuditLogger};
+use git_proxy_mcp::security::guards::DEFAULT_PROTECTED_BRANCHES;
+use git_proxy_mcp::security::{AuditEvent, AuditLogger, SecurityCheckResult};
 
 /// Secure Git proxy MCP server for AI assistants.
 ///
@@ -26,8 +29,8 @@ use git_proxy_mcp::security::{AuditEvent, AuditLogger};
 #[command(author, version, about, long_about = None)]
 struct Args {
     /// Path to configuration file
-    #[arg(short, long, value_name = "FILE")]
-    config: Option<std::path::PathBuf>,
+    #[arg(short, long, value_name = "FILE", global = true)]
+    config: Option<PathBuf>,
 
     /// Increase logging verbosity (-v for info, -vv for debug, -vvv for trace)
     #[arg(short, long, action = clap::ArgAction::Count)]
@@ -36,6 +39,39 @@ struct Args {
     /// Decrease logging verbosity (only show errors)
     #[arg(short, long)]
     quiet: bool,
+
+    /// Subcommand to run instead of starting the server
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Subcommands that run instead of the MCP server.
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Load and validate the configuration, then print the effective settings
+    CheckConfig {
+        /// Print the effective configuration as JSON only
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show how the security policy treats a git command, without running it
+    Explain {
+        /// Working directory the command would run in (before the git command)
+        #[arg(long, value_name = "DIR")]
+        cwd: Option<PathBuf>,
+
+        /// Git command to check and its arguments (e.g., push --force origin
+        /// main), taken as given: options after the command are git's
+        #[arg(
+            value_name = "GIT_COMMAND",
+            required = true,
+            num_args = 1..,
+            trailing_var_arg = true,
+            allow_hyphen_values = true
+        )]
+        git: Vec<String>,
+    },
 }
 
 /// Determines the log level from CLI arguments.
@@ -69,14 +105,10 @@ fn init_tracing(level: Level) {
         .init();
 }
 
-/// Entry point for the git-proxy-mcp server.
-fn main() -> ExitCode {
-    let args = Args::parse();
-
-    // Load configuration first to get log level
-    let config_path = args.config.as_deref();
-    let cfg = match config::load_config(config_path) {
-        Ok(cfg) => cfg,
+/// Loads the configuration, printing any error to stderr.
+fn load_config_or_report(config_path: Option<&Path>) -> Option<config::Config> {
+    match config::load_config(config_path) {
+        Ok(cfg) => Some(cfg),
         Err(e) => {
             eprintln!("Configuration error: {e}");
             if config_path.is_none() {
@@ -85,10 +117,174 @@ fn main() -> ExitCode {
                     eprintln!("Create one based on config/example-config.json");
                 }
             }
+            None
+        }
+    }
+}
+
+/// Maps the loaded configuration onto the server's security settings.
+fn security_config_from(cfg: &config::Config) -> SecurityConfig {
+    SecurityConfig {
+        allow_force_push: cfg.security.allow_force_push,
+        protected_branches: cfg.security.protected_branches.clone(),
+        protected_tags: cfg.security.protected_tags.clone(),
+        forbid_tag_creation: cfg.security.forbid_tag_creation,
+        forbid_tag_deletion: cfg.security.forbid_tag_deletion,
+        forbid_tag_force_update: cfg.security.forbid_tag_force_update,
+        repo_allowlist: cfg.security.repo_allowlist.clone(),
+        repo_blocklist: cfg.security.repo_blocklist.clone(),
+        url_credentials: cfg.security.url_credentials,
+        allowed_protocols: Some(cfg.security.allowed_protocols()),
+        workspace_roots: cfg.security.workspace_roots.clone(),
+        rate_limit: cfg.limits.rate_limit.clone(),
+    }
+}
+
+/// Builds the server from the configuration.
+///
+/// `explain` uses the same server as the real one, so a dry run applies the
+/// policy the server enforces, including the repository-local config policy.
+fn build_server(
+    cfg: &config::Config,
+    sanitiser: OutputSanitiser,
+    audit_logger: AuditLogger,
+) -> McpServer {
+    // Git executor with configured timeouts, output limits, environment,
+    // repository-local config handling and sanitiser
+    let executor = GitExecutor::with_limits(
+        cfg.timeouts.request_timeout(),
+        cfg.limits.max_output_bytes(),
+    )
+    .with_lock_timeout(cfg.timeouts.repository_lock_timeout())
+    .with_allowed_protocols(cfg.security.allowed_protocols())
+    .with_environment(cfg.security.environment.build())
+    .with_local_config(cfg.security.local_config.build())
+    .with_sanitiser(sanitiser);
+
+    McpServer::new(executor, security_config_from(cfg), audit_logger)
+        .with_max_concurrent_requests(cfg.limits.max_concurrent_requests)
+        .with_max_queued_requests(cfg.limits.max_queued_requests)
+        .with_generic_git_tool(cfg.tools.generic_git_tool)
+}
+
+/// Runs the `explain` subcommand.
+///
+/// Evaluates command validation and every security guard against the given
+/// arguments, exactly as the server would, and prints each verdict. Git is
+/// never executed. Exits non-zero if the command would be blocked.
+fn explain(
+    config_path: Option<&Path>,
+    command: &str,
+    args: &[String],
+    cwd: Option<&Path>,
+) -> ExitCode {
+    let Some(cfg) = load_config_or_report(config_path) else {
+        return ExitCode::FAILURE;
+    };
+    let sanitiser = match cfg.security.sanitiser.build() {
+        Ok(sanitiser) => sanitiser,
+        Err(e) => {
+            eprintln!("Failed to build output sanitiser: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let server = build_server(&cfg, sanitiser, AuditLogger::disabled());
+    let explanation = server.explain(command, args, cwd);
+
+    let invocation = std::iter::once(command)
+        .chain(args.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ");
+    println!("git {invocation}");
+    match &explanation.validation {
+        Ok(()) => println!("  command validation: allowed"),
+        Err(reason) => println!("  command validation: BLOCKED: {reason}"),
+    }
+    for verdict in &explanation.verdicts {
+        match &verdict.result {
+            SecurityCheckResult::Allowed => println!("  {}: allowed", verdict.guard),
+            SecurityCheckResult::Blocked { reason } => {
+                println!("  {}: BLOCKED: {reason}", verdict.guard);
+            }
+        }
+    }
+
+    if explanation.is_allowed() {
+        println!("Verdict: allowed");
+        ExitCode::SUCCESS
+    } else {
+        println!("Verdict: blocked");
+        ExitCode::FAILURE
+    }
+}
+
+/// Runs the `check-config` subcommand.
+///
+/// Loads and validates the configuration, then prints the effective settings
+/// (file values merged with defaults), with the values of
+/// `security.environment.set` redacted. Exits non-zero if the configuration
+/// cannot be loaded or fails validation.
+fn check_config(config_path: Option<&Path>, json: bool) -> ExitCode {
+    let Some(mut cfg) = load_config_or_report(config_path) else {
+        return ExitCode::FAILURE;
+    };
+
+    // Mirror the server: no configured branches means the built-in defaults apply
+    if cfg.security.protected_branches.is_empty() {
+        cfg.security.protected_branches = DEFAULT_PROTECTED_BRANCHES
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+    }
+    cfg.security.environment.redact_values();
+
+    let effective = match serde_json::to_string_pretty(&cfg) {
+        Ok(effective) => effective,
+        Err(e) => {
+            eprintln!("Failed to serialise configuration: {e}");
             return ExitCode::FAILURE;
         }
     };
 
+    if json {
+        println!("{effective}");
+        return ExitCode::SUCCESS;
+    }
+
+    let path = config_path
+        .map(Path::to_path_buf)
+        .or_else(config::default_config_path);
+    if let Some(path) = path {
+        println!("Configuration file: {}", path.display());
+    }
+    println!("Configuration is valid.\n");
+    println!("Effective settings:");
+    println!("{effective}");
+
+    ExitCode::SUCCESS
+}
+
+/// Entry point for the git-proxy-mcp server.
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    let config_path = args.config.as_deref();
+
+    match args.command {
+        Some(Command::CheckConfig { json }) => return check_config(config_path, json),
+        Some(Command::Explain { cwd, git }) => {
+            let (command, args) = git.split_first().expect("git command is required");
+            return explain(config_path, command, args, cwd.as_deref());
+        }
+        None => {}
+    }
+
+    // Load configuration first to get log level
+    let Some(cfg) = load_config_or_report(config_path) else {
+        return ExitCode::FAILURE;
+    };
+
     // Initialise logging
     let log_level = get_log_level(args.verbose, args.quiet, &cfg.logging.level);
     init_tracing(log_level);
@@ -98,12 +294,25 @@ fn main() -> ExitCode {
         "Starting git-proxy-mcp server"
     );
 
+    // Build the sanitiser shared by command output and the audit log
+    let sanitiser = match cfg.security.sanitiser.build() {
+        Ok(sanitiser) => sanitiser,
+        Err(e) => {
+            error!(error = %e, "Failed to build output sanitiser");
+            return ExitCode::FAILURE;
+        }
+    };
+    info!(
+        rules = ?sanitiser.rule_names(),
+        "Output sanitiser ready"
+    );
+
     // Create audit logger
     let audit_logger = if let Some(path) = &cfg.logging.audit_log_path {
         match AuditLogger::new(path) {
             Ok(logger) => {
                 info!(path = %path.display(), "Audit logging enabled");
-                logger
+                logger.with_sanitiser(sanitiser.clone())
             }
             Err(e) => {
                 error!(error = %e, "Failed to create audit logger");
@@ -118,36 +327,33 @@ fn main() -> ExitCode {
     // Log server start
     audit_logger.log_silent(&AuditEvent::server_started());
 
-    // Build security config
-    let security_config = SecurityConfig {
-        allow_force_push: cfg.security.allow_force_push,
-        protected_branches: cfg.security.protected_branches,
-        repo_allowlist: cfg.security.repo_allowlist,
-        repo_blocklist: cfg.security.repo_blocklist,
-    };
-
     info!(
-        force_push = security_config.allow_force_push,
-        protected_branches = ?security_config.protected_branches,
+        force_push = cfg.security.allow_force_push,
+        protected_branches = ?cfg.security.protected_branches,
+        protected_tags = ?cfg.security.protected_tags,
+        url_credentials = ?cfg.security.url_credentials,
+        allowed_protocols = ?cfg.security.allowed_protocols(),
         request_timeout_secs = cfg.timeouts.request_timeout_secs,
+        repository_lock_timeout_secs = cfg.timeouts.repository_lock_timeout_secs,
         max_output_bytes = cfg.limits.max_output_bytes,
+        max_concurrent_requests = cfg.limits.max_concurrent_requests,
+        max_queued_requests = cfg.limits.max_queued_requests,
+        generic_git_tool = cfg.tools.generic_git_tool,
+        rate_limit_unlimited = cfg.limits.rate_limit.unlimited,
+        rate_limit_burst = cfg.limits.rate_limit.max_burst,
+        rate_limit_refill_rate = cfg.limits.rate_limit.refill_rate,
         "Configuration loaded"
     );
 
-    // Create git executor with configured timeout and output limits
-    let executor = GitExecutor::with_limits(
-        cfg.timeouts.request_timeout(),
-        cfg.limits.max_output_bytes(),
-    );
-
     // Create MCP server
-    let mut server = McpServer::new(executor, security_config, audit_logger);
+    let server = build_server(&cfg, sanitiser, audit_logger);
 
     info!("MCP server ready, waiting for client connection...");
     info!("Note: Authentication uses your existing Git credential configuration");
 
     // Run the server
-    // Using current-thread runtime since MCP uses single-connection stdio transport
+    // Using current-thread runtime since MCP uses single-connection stdio transport.
+    // Concurrent tool calls are tasks on this thread; git itself runs in subprocesses.
     let runtime = tokio::runtime::Builder::new_current_thread()
         .enable_all()
         .build()
@@ -175,4 +381,83 @@ mod tests {
         use clap::CommandFactory;
         Args::command().debug_assert();
     }
+
+    #[test]
+    fn parse_check_config_subcommand() {
+        let args = Args::try_parse_from([
+            "git-proxy-mcp",
+            "check-config",
+            "--json",
+            "--config",
+            "c.json",
+        ])
+        .unwrap();
+
+        assert!(matches!(
+            args.command,
+            Some(Command::CheckConfig { json: true })
+        ));
+        assert_eq!(args.config.as_deref(), Some(Path::new("c.json")));
+    }
+
+    #[test]
+    fn parse_explain_subcommand() {
+        let args = Args::try_parse_from([
+            "git-proxy-mcp",
+            "explain",
+            "--cwd",
+            "/tmp/repo",
+            "push",
+            "--force",
+            "origin",
+            "main",
+        ])
+        .unwrap();
+
+        let Some(Command::Explain { cwd, git }) = args.command else {
+            panic!("expected explain subcommand");
+        };
+        assert_eq!(git, ["push", "--force", "origin", "main"]);
+        assert_eq!(cwd.as_deref(), Some(Path::new("/tmp/repo")));
+    }
+
+    #[test]
+    fn explain_passes_options_after_the_command_to_git() {
+        let args = Args::try_parse_from([
+            "git-proxy-mcp",
+            "--config",
+            "c.json",
+            "explain",
+            "clone",
+            "--config",
+            "core.sshCommand=x",
+            "https://github.com/org/repo.git",
+            "--cwd",
+            "dir",
+        ])
+        .unwrap();
+
+        assert_eq!(args.config.as_deref(), Some(Path::new("c.json")));
+        let Some(Command::Explain { cwd, git }) = args.command else {
+            panic!("expected explain subcommand");
+        };
+        assert!(cwd.is_none());
+        assert_eq!(
+            git,
+            [
+                "clone",
+                "--config",
+                "core.sshCommand=x",
+                "https://github.com/org/repo.git",
+                "--cwd",
+                "dir"
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_without_subcommand_runs_server() {
+        let args = Args::try_parse_from(["git-proxy-mcp", "-v"]).unwrap();
+        assert!(args.command.is_none());
+    }
 }
@@ -32,9 +32,12 @@
 //! This implementation targets MCP protocol version 2024-11-05.
 
 pub mod protocol;
+pub mod scheduler;
 pub mod server;
+pub mod tools;
 pub mod transport;
 
 pub use protocol::{JsonRpcError, JsonRpcRequest, JsonRpcResponse, MCP_PROTOCOL_VERSION};
-pub use server::{McpServer, SecurityConfig};
+pub use server::{GitToolOutput, GuardVerdict, McpServer, PolicyExplanation, SecurityConfig};
+pub use tools::{GitTool, ToolInvocation};
 pub use transport::StdioTransport;
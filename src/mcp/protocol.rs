@@ -26,7 +26,7 @@ pub const SERVER_NAME: &str = "git-proxy-mcp";
 /// A JSON-RPC 2.0 request ID.
 ///
 /// Per the MCP specification, IDs must be strings or integers, never `null`.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum RequestId {
     /// Numeric request ID.
@@ -44,6 +44,20 @@ impl std::fmt::Display for RequestId {
     }
 }
 
+/// A progress token supplied by the client in a request's `_meta`.
+///
+/// Like request IDs, progress tokens are strings or integers.
+pub type ProgressToken = RequestId;
+
+/// Request metadata carried in the `_meta` field of request parameters.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestMeta {
+    /// Token the client wants progress notifications tagged with.
+    #[serde(default)]
+    pub progress_token: Option<ProgressToken>,
+}
+
 /// A JSON-RPC 2.0 request message.
 ///
 /// Requests expect a response from the server.
@@ -82,7 +96,7 @@ impl JsonRpcRequest {
 /// A JSON-RPC 2.0 notification message.
 ///
 /// Notifications do not have an ID and do not expect a response.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JsonRpcNotification {
     /// Must be "2.0".
     pub jsonrpc: String,
@@ -91,10 +105,22 @@ pub struct JsonRpcNotification {
     pub method: String,
 
     /// Optional parameters for the notification.
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub params: Option<Value>,
 }
 
+impl JsonRpcNotification {
+    /// Creates a new notification to send to the client.
+    #[must_use]
+    pub fn new(method: impl Into<String>, params: Option<Value>) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            method: method.into(),
+            params,
+        }
+    }
+}
+
 /// A successful JSON-RPC 2.0 response.
 #[derive(Debug, Clone, Serialize)]
 pub struct JsonRpcResponse {
@@ -445,6 +471,33 @@ mod tests {
         assert!(json.contains("unknown/method"));
     }
 
+    #[test]
+    fn serialise_notification() {
+        let notification = JsonRpcNotification::new(
+            "notifications/progress",
+            Some(serde_json::json!({"progress": 1})),
+        );
+        let json = serde_json::to_string(&notification).unwrap();
+        assert!(json.contains(r#""jsonrpc":"2.0""#));
+        assert!(json.contains(r#""method":"notifications/progress""#));
+        assert!(!json.contains(r#""id""#));
+
+        let bare = JsonRpcNotification::new("notifications/initialized", None);
+        assert!(!serde_json::to_string(&bare).unwrap().contains("params"));
+    }
+
+    #[test]
+    fn deserialise_request_meta() {
+        let meta: RequestMeta = serde_json::from_str(r#"{"progressToken": "abc"}"#).unwrap();
+        assert_eq!(
+            meta.progress_token,
+            Some(ProgressToken::String("abc".to_string()))
+        );
+
+        let meta: RequestMeta = serde_json::from_str("{}").unwrap();
+        assert!(meta.progress_token.is_none());
+    }
+
     #[test]
     fn request_id_display() {
         assert_eq!(format!("{}", RequestId::Number(42)), "42");
@@ -0,0 +1,235 @@
+//! Scheduling of concurrent tool calls.
+//!
+//! Tool calls run as concurrent tasks so that a long clone does not block
+//! `ping` or other calls. Two limits apply to them:
+//!
+//! - At most `limits.max_concurrent_requests` git commands run at once
+//! - Commands on the same working directory run one at a time, so two git
+//!   processes never work on one checkout simultaneously
+//!
+//! The working directory lock is taken before a concurrency slot, so calls
+//! waiting for a busy directory do not hold slots other directories could use.
+//! The server refuses calls once `limits.max_queued_requests` are already
+//! waiting, so a flood of calls cannot pile up unbounded tasks.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard, OwnedSemaphorePermit, Semaphore};
+
+/// Default maximum number of tool calls executed at once.
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 4;
+
+/// Default maximum number of tool calls waiting to execute. Calls beyond it
+/// are refused rather than queued.
+pub const DEFAULT_MAX_QUEUED_REQUESTS: usize = 16;
+
+/// Schedules git commands from concurrent tool calls.
+#[derive(Debug)]
+pub struct Scheduler {
+    /// Slots for concurrently running commands.
+    slots: Arc<Semaphore>,
+    /// Maximum number of concurrently running commands.
+    max_concurrent: usize,
+    /// One lock per working directory currently in use.
+    directories: Mutex<HashMap<PathBuf, Arc<AsyncMutex<()>>>>,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_CONCURRENT_REQUESTS)
+    }
+}
+
+impl Scheduler {
+    /// Creates a scheduler allowing `max_concurrent` commands at once.
+    ///
+    /// A limit of zero is treated as one.
+    #[must_use]
+    pub fn new(max_concurrent: usize) -> Self {
+        let max_concurrent = max_concurrent.max(1);
+        Self {
+            slots: Arc::new(Semaphore::new(max_concurrent)),
+            max_concurrent,
+            directories: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the maximum number of concurrently running commands.
+    #[must_use]
+    pub const fn max_concurrent(&self) -> usize {
+        self.max_concurrent
+    }
+
+    /// Waits until a command may run.
+    ///
+    /// If `working_dir` is given, waits for exclusive use of that directory
+    /// first, then for a free concurrency slot. The returned guard must be
+    /// held for as long as the command runs.
+    pub async fn acquire(&self, working_dir: Option<&Path>) -> ScheduleGuard<'_> {
+        let directory = match working_dir {
+            Some(dir) => Some(self.lock_directory(dir).await),
+            None => None,
+        };
+
+        // The semaphore is never closed, so acquiring cannot fail
+        let slot = Arc::clone(&self.slots).acquire_owned().await.ok();
+
+        ScheduleGuard {
+            _slot: slot,
+            directory,
+        }
+    }
+
+    /// Returns the number of working directories currently locked or awaited.
+    #[must_use]
+    pub fn busy_directories(&self) -> usize {
+        self.lock_directories().len()
+    }
+
+    /// Waits for exclusive use of a working directory.
+    async fn lock_directory(&self, dir: &Path) -> DirectoryGuard<'_> {
+        // Different spellings of the same directory must share a lock
+        let key = std::fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+
+        let lock = Arc::clone(self.lock_directories().entry(key.clone()).or_default());
+        let guard = lock.lock_owned().await;
+
+        DirectoryGuard {
+            scheduler: self,
+            key,
+            guard: Some(guard),
+        }
+    }
+
+    /// Locks the directory map, recovering from poison if necessary.
+    fn lock_directories(&self) -> MutexGuard<'_, HashMap<PathBuf, Arc<AsyncMutex<()>>>> {
+        self.directories.lock().unwrap_or_else(|poisoned| {
+            tracing::warn!("Scheduler directory mutex was poisoned; recovering");
+            poisoned.into_inner()
+        })
+    }
+}
+
+/// Permission to run a command, released when dropped.
+#[derive(Debug)]
+pub struct ScheduleGuard<'a> {
+    /// Concurrency slot.
+    _slot: Option<OwnedSemaphorePermit>,
+    /// Exclusive use of the working directory, if the command has one.
+    directory: Option<DirectoryGuard<'a>>,
+}
+
+impl ScheduleGuard<'_> {
+    /// Returns whether this guard holds a working directory lock.
+    #[must_use]
+    pub const fn holds_directory(&self) -> bool {
+        self.directory.is_some()
+    }
+}
+
+/// Exclusive use of a working directory, released when dropped.
+#[derive(Debug)]
+struct DirectoryGuard<'a> {
+    /// Scheduler owning the lock.
+    scheduler: &'a Scheduler,
+    /// Key of the directory in the scheduler's map.
+    key: PathBuf,
+    /// Guard of the directory's lock.
+    guard: Option<OwnedMutexGuard<()>>,
+}
+
+impl Drop for DirectoryGuard<'_> {
+    fn drop(&mut self) {
+        let mut directories = self.scheduler.lock_directories();
+        drop(self.guard.take());
+
+        // Forget the lock once nobody holds or waits for it, so the map only
+        // contains directories in use
+        if directories
+            .get(&self.key)
+            .is_some_and(|lock| Arc::strong_count(lock) == 1)
+        {
+            directories.remove(&self.key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn same_directory_is_serialised() {
+        let scheduler = Scheduler::new(4);
+        let dir = std::env::temp_dir();
+
+        let first = scheduler.acquire(Some(&dir)).await;
+        assert!(first.holds_directory());
+
+        // A second command on the same directory waits
+        let waiting = scheduler.acquire(Some(&dir));
+        assert!(tokio::time::timeout(Duration::from_millis(50), waiting)
+            .await
+            .is_err());
+
+        drop(first);
+        assert!(scheduler.acquire(Some(&dir)).await.holds_directory());
+    }
+
+    #[tokio::test]
+    async fn different_directories_run_concurrently() {
+        let scheduler = Scheduler::new(4);
+        let temp = tempfile::TempDir::new().unwrap();
+        let one = temp.path().join("one");
+        let two = temp.path().join("two");
+
+        let first = scheduler.acquire(Some(&one)).await;
+        let second = tokio::time::timeout(Duration::from_millis(50), scheduler.acquire(Some(&two)))
+            .await
+            .expect("a different directory should not wait");
+        assert_eq!(scheduler.busy_directories(), 2);
+
+        drop((first, second));
+    }
+
+    #[tokio::test]
+    async fn concurrency_limit_is_enforced() {
+        let scheduler = Scheduler::new(1);
+
+        let first = scheduler.acquire(None).await;
+        assert!(!first.holds_directory());
+
+        let waiting = scheduler.acquire(None);
+        assert!(tokio::time::timeout(Duration::from_millis(50), waiting)
+            .await
+            .is_err());
+
+        drop(first);
+        assert!(!scheduler.acquire(None).await.holds_directory());
+    }
+
+    #[tokio::test]
+    async fn released_directories_are_forgotten() {
+        let scheduler = Scheduler::new(2);
+        let dir = std::env::temp_dir();
+
+        let guard = scheduler.acquire(Some(&dir)).await;
+        assert_eq!(scheduler.busy_directories(), 1);
+
+        drop(guard);
+        assert_eq!(scheduler.busy_directories(), 0);
+    }
+
+    #[test]
+    fn zero_limit_is_treated_as_one() {
+        assert_eq!(Scheduler::new(0).max_concurrent(), 1);
+        assert_eq!(
+            Scheduler::default().max_concurrent(),
+            DEFAULT_MAX_CONCURRENT_REQUESTS
+        );
+    }
+}
@@ -0,0 +1,755 @@
+//! Typed git tools.
+//!
+//! Besides the generic `git` tool, whose `args` is a free-form list, the
+//! server exposes one tool per remote command with a typed input schema:
+//!
+//! | Tool | Command |
+//! |------|---------|
+//! | `git_clone` | `git clone` |
+//! | `git_fetch` | `git fetch` |
+//! | `git_pull` | `git pull` |
+//! | `git_push` | `git push` |
+//! | `git_ls_remote` | `git ls-remote` |
+//!
+//! Typed arguments are translated into a command line in a fixed order, with
+//! option values attached (`--depth=1`), so models no longer have to guess
+//! argument order. The resulting [`ToolInvocation`] goes through the same
+//! validation and security guards as the generic tool.
+
+use std::path::PathBuf;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::git::refs::{RefFilter, RefKind};
+
+/// A git command line produced from a tool call's arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolInvocation {
+    /// The git subcommand (e.g., "clone").
+    pub command: String,
+    /// Arguments to pass to the subcommand.
+    pub args: Vec<String>,
+    /// Working directory, if given.
+    pub working_dir: Option<PathBuf>,
+    /// For `ls-remote`, the refs to keep when the listing is parsed into
+    /// structured refs. `None` if the output is returned as text only.
+    pub ref_listing: Option<RefFilter>,
+}
+
+/// The git tools offered by the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitTool {
+    /// The generic `git` tool taking a command and free-form arguments.
+    Generic,
+    /// `git_clone`.
+    Clone,
+    /// `git_fetch`.
+    Fetch,
+    /// `git_pull`.
+    Pull,
+    /// `git_push`.
+    Push,
+    /// `git_ls_remote`.
+    LsRemote,
+}
+
+impl GitTool {
+    /// All tools, in the order they are listed.
+    pub const ALL: [Self; 6] = [
+        Self::Clone,
+        Self::Fetch,
+        Self::Pull,
+        Self::Push,
+        Self::LsRemote,
+        Self::Generic,
+    ];
+
+    /// Looks up a tool by name.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|tool| tool.name() == name)
+    }
+
+    /// Returns the tool's name.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Generic => "git",
+            Self::Clone => "git_clone",
+            Self::Fetch => "git_fetch",
+            Self::Pull => "git_pull",
+            Self::Push => "git_push",
+            Self::LsRemote => "git_ls_remote",
+        }
+    }
+
+    /// Returns the tool's description.
+    #[must_use]
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::Generic => {
+                "Execute remote Git commands using your existing Git credential configuration. \
+                 Only remote operations are supported: clone, fetch, pull, push, ls-remote. \
+                 Local commands (status, log, diff, commit, etc.) should be run directly. \
+                 Authentication is handled by your system's credential helpers and SSH agent."
+            }
+            Self::Clone => "Clone a remote Git repository into a new directory.",
+            Self::Fetch => "Download objects and refs from a remote into an existing repository.",
+            Self::Pull => "Fetch from a remote and integrate the changes into the current branch.",
+            Self::Push => "Update refs on a remote with local commits.",
+            Self::LsRemote => {
+                "List the references (branches, tags) in a remote repository. \
+                 The structured result contains the parsed refs; use 'filter' and 'kinds' \
+                 to list only the refs you need from large repositories."
+            }
+        }
+    }
+
+    /// Returns the JSON Schema for the tool's arguments.
+    #[must_use]
+    pub fn input_schema(self) -> Value {
+        match self {
+            Self::Generic => json!({
+                "type": "object",
+                "properties": {
+                    "command": {
+                        "type": "string",
+                        "enum": ["clone", "fetch", "ls-remote", "pull", "push"],
+                        "description": "The remote Git command to execute"
+                    },
+                    "args": {
+                        "type": "array",
+                        "items": {"type": "string"},
+                        "description": "Arguments to pass to the Git command"
+                    },
+                    "cwd": {
+                        "type": "string",
+                        "description": "Working directory for the Git command (optional)"
+                    }
+                },
+                "required": ["command"]
+            }),
+            Self::Clone => object_schema(
+                &json!({
+                    "url": string_property("URL of the repository to clone"),
+                    "directory": string_property(
+                        "Directory to clone into, inside cwd and empty if it exists (defaults to the repository name)"
+                    ),
+                    "branch": string_property("Branch to check out instead of the remote's HEAD"),
+                    "depth": depth_property(),
+                    "single_branch": bool_property("Only fetch the history of one branch"),
+                    "cwd": cwd_property(),
+                }),
+                &["url"],
+            ),
+            Self::Fetch => object_schema(
+                &json!({
+                    "remote": remote_property(),
+                    "refspecs": refspecs_property("Refs to fetch (requires remote)"),
+                    "all": bool_property("Fetch all remotes (cannot be combined with remote)"),
+                    "prune": bool_property(
+                        "Remove remote-tracking refs that no longer exist on the remote"
+                    ),
+                    "tags": bool_property("Fetch all tags"),
+                    "depth": depth_property(),
+                    "cwd": cwd_property(),
+                }),
+                &[],
+            ),
+            Self::Pull => object_schema(
+                &json!({
+                    "remote": remote_property(),
+                    "branch": string_property("Remote branch to pull (requires remote)"),
+                    "rebase": bool_property("Rebase local commits instead of merging"),
+                    "ff_only": bool_property("Only update if the branch can be fast-forwarded"),
+                    "depth": depth_property(),
+                    "cwd": cwd_property(),
+                }),
+                &[],
+            ),
+            Self::Push => object_schema(
+                &json!({
+                    "remote": remote_property(),
+                    "refspecs": refspecs_property(
+                        "Refs to push, e.g. \"main\" or \"feature:feature\" (requires remote)"
+                    ),
+                    "tags": bool_property("Push all tags"),
+                    "set_upstream": bool_property("Set the remote branch as upstream"),
+                    "force_with_lease": bool_property(
+                        "Overwrite remote refs only if they are as last fetched"
+                    ),
+                    "dry_run": bool_property("Show what would be pushed without pushing"),
+                    "cwd": cwd_property(),
+                }),
+                &[],
+            ),
+            Self::LsRemote => object_schema(
+                &json!({
+                    "remote": string_property(
+                        "Remote name or URL (defaults to the current branch's remote)"
+                    ),
+                    "heads": bool_property("Only list branches"),
+                    "tags": bool_property("Only list tags"),
+                    "patterns": refspecs_property(
+                        "Only list refs whose names end with these patterns (passed to git)"
+                    ),
+                    "symref": bool_property("Report the branch HEAD points to"),
+                    "filter": refspecs_property(
+                        "Glob patterns on full or short ref names, e.g. \"release/*\" or \"v1.*\""
+                    ),
+                    "kinds": {
+                        "type": "array",
+                        "items": {
+                            "type": "string",
+                            "enum": ["branch", "tag", "peeled-tag", "head", "pull", "other"]
+                        },
+                        "description": "Only list refs of these kinds"
+                    },
+                    "cwd": cwd_property(),
+                }),
+                &[],
+            ),
+        }
+    }
+
+    /// Translates a tool call's arguments into a git command line.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message describing the problem if the arguments do not
+    /// match the tool's schema or contradict each other.
+    pub fn invocation(self, arguments: &Value) -> Result<ToolInvocation, String> {
+        match self {
+            Self::Generic => generic_invocation(arguments),
+            Self::Clone => parse::<CloneArgs>(arguments)?.into_invocation(),
+            Self::Fetch => parse::<FetchArgs>(arguments)?.into_invocation(),
+            Self::Pull => parse::<PullArgs>(arguments)?.into_invocation(),
+            Self::Push => parse::<PushArgs>(arguments)?.into_invocation(),
+            Self::LsRemote => parse::<LsRemoteArgs>(arguments)?.into_invocation(),
+        }
+    }
+}
+
+/// Arguments of `git_clone`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CloneArgs {
+    url: String,
+    #[serde(default)]
+    directory: Option<String>,
+    #[serde(default)]
+    branch: Option<String>,
+    #[serde(default)]
+    depth: Option<u32>,
+    #[serde(default)]
+    single_branch: bool,
+    #[serde(default)]
+    cwd: Option<PathBuf>,
+}
+
+impl CloneArgs {
+    fn into_invocation(self) -> Result<ToolInvocation, String> {
+        let mut args = Vec::new();
+        push_value(&mut args, "--branch", "branch", self.branch)?;
+        push_depth(&mut args, self.depth)?;
+        push_flag(&mut args, "--single-branch", self.single_branch);
+        push_positional(&mut args, "url", self.url)?;
+        if let Some(directory) = self.directory {
+            push_positional(&mut args, "directory", directory)?;
+        }
+        Ok(invocation("clone", args, self.cwd))
+    }
+}
+
+/// Arguments of `git_fetch`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FetchArgs {
+    #[serde(default)]
+    remote: Option<String>,
+    #[serde(default)]
+    refspecs: Vec<String>,
+    #[serde(default)]
+    all: bool,
+    #[serde(default)]
+    prune: bool,
+    #[serde(default)]
+    tags: bool,
+    #[serde(default)]
+    depth: Option<u32>,
+    #[serde(default)]
+    cwd: Option<PathBuf>,
+}
+
+impl FetchArgs {
+    fn into_invocation(self) -> Result<ToolInvocation, String> {
+        if self.all && self.remote.is_some() {
+            return Err("'all' cannot be combined with 'remote'".to_string());
+        }
+        let mut args = Vec::new();
+        push_flag(&mut args, "--all", self.all);
+        push_flag(&mut args, "--prune", self.prune);
+        push_flag(&mut args, "--tags", self.tags);
+        push_depth(&mut args, self.depth)?;
+        push_remote_and_refs(&mut args, self.remote, "refspecs", self.refspecs)?;
+        Ok(invocation("fetch", args, self.cwd))
+    }
+}
+
+/// Arguments of `git_pull`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PullArgs {
+    #[serde(default)]
+    remote: Option<String>,
+    #[serde(default)]
+    branch: Option<String>,
+    #[serde(default)]
+    rebase: bool,
+    #[serde(default)]
+    ff_only: bool,
+    #[serde(default)]
+    depth: Option<u32>,
+    #[serde(default)]
+    cwd: Option<PathBuf>,
+}
+
+impl PullArgs {
+    fn into_invocation(self) -> Result<ToolInvocation, String> {
+        if self.rebase && self.ff_only {
+            return Err("'rebase' cannot be combined with 'ff_only'".to_string());
+        }
+        let mut args = Vec::new();
+        push_flag(&mut args, "--rebase", self.rebase);
+        push_flag(&mut args, "--ff-only", self.ff_only);
+        push_depth(&mut args, self.depth)?;
+        let branches = self.branch.into_iter().collect();
+        push_remote_and_refs(&mut args, self.remote, "branch", branches)?;
+        Ok(invocation("pull", args, self.cwd))
+    }
+}
+
+/// Arguments of `git_push`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[allow(clippy::struct_excessive_bools)] // Mirrors git's independent push flags
+struct PushArgs {
+    #[serde(default)]
+    remote: Option<String>,
+    #[serde(default)]
+    refspecs: Vec<String>,
+    #[serde(default)]
+    tags: bool,
+    #[serde(default)]
+    set_upstream: bool,
+    #[serde(default)]
+    force_with_lease: bool,
+    #[serde(default)]
+    dry_run: bool,
+    #[serde(default)]
+    cwd: Option<PathBuf>,
+}
+
+impl PushArgs {
+    fn into_invocation(self) -> Result<ToolInvocation, String> {
+        let mut args = Vec::new();
+        push_flag(&mut args, "--tags", self.tags);
+        push_flag(&mut args, "--set-upstream", self.set_upstream);
+        push_flag(&mut args, "--force-with-lease", self.force_with_lease);
+        push_flag(&mut args, "--dry-run", self.dry_run);
+        push_remote_and_refs(&mut args, self.remote, "refspecs", self.refspecs)?;
+        Ok(invocation("push", args, self.cwd))
+    }
+}
+
+/// Arguments of `git_ls_remote`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LsRemoteArgs {
+    #[serde(default)]
+    remote: Option<String>,
+    #[serde(default)]
+    heads: bool,
+    #[serde(default)]
+    tags: bool,
+    #[serde(default)]
+    patterns: Vec<String>,
+    #[serde(default)]
+    symref: bool,
+    #[serde(default)]
+    filter: Vec<String>,
+    #[serde(default)]
+    kinds: Vec<RefKind>,
+    #[serde(default)]
+    cwd: Option<PathBuf>,
+}
+
+impl LsRemoteArgs {
+    fn into_invocation(self) -> Result<ToolInvocation, String> {
+        let ref_filter = RefFilter::new(&self.filter, self.kinds)
+            .map_err(|e| format!("Invalid 'filter' pattern: {e}"))?;
+
+        let mut args = Vec::new();
+        push_flag(&mut args, "--heads", self.heads);
+        push_flag(&mut args, "--tags", self.tags);
+        push_flag(&mut args, "--symref", self.symref);
+        push_remote_and_refs(&mut args, self.remote, "patterns", self.patterns)?;
+
+        let mut invocation = invocation("ls-remote", args, self.cwd);
+        invocation.ref_listing = Some(ref_filter);
+        Ok(invocation)
+    }
+}
+
+/// Extracts the command, arguments and working directory from the generic
+/// git tool's arguments.
+fn generic_invocation(arguments: &Value) -> Result<ToolInvocation, String> {
+    let command = arguments
+        .get("command")
+        .and_then(Value::as_str)
+        .filter(|cmd| !cmd.is_empty())
+        .ok_or_else(|| "Missing required 'command' argument".to_string())?;
+
+    let args = arguments
+        .get("args")
+        .and_then(Value::as_array)
+        .map(|arr| {
+            arr.iter()
+                .filter_map(Value::as_str)
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let working_dir = arguments
+        .get("cwd")
+        .and_then(Value::as_str)
+        .map(PathBuf::from);
+
+    Ok(ToolInvocation {
+        command: command.to_string(),
+        args,
+        working_dir,
+        ref_listing: None,
+    })
+}
+
+/// Deserialises a typed tool's arguments.
+fn parse<T: DeserializeOwned>(arguments: &Value) -> Result<T, String> {
+    // A call without arguments is the same as one with no fields set
+    let arguments = if arguments.is_null() {
+        json!({})
+    } else {
+        arguments.clone()
+    };
+    serde_json::from_value(arguments).map_err(|e| format!("Invalid arguments: {e}"))
+}
+
+/// Builds an invocation of a typed tool.
+fn invocation(command: &str, args: Vec<String>, working_dir: Option<PathBuf>) -> ToolInvocation {
+    ToolInvocation {
+        command: command.to_string(),
+        args,
+        working_dir,
+        ref_listing: None,
+    }
+}
+
+/// Appends `flag` if it is set.
+fn push_flag(args: &mut Vec<String>, flag: &str, set: bool) {
+    if set {
+        args.push(flag.to_string());
+    }
+}
+
+/// Appends `--option=value` if a value is given.
+fn push_value(
+    args: &mut Vec<String>,
+    option: &str,
+    field: &str,
+    value: Option<String>,
+) -> Result<(), String> {
+    if let Some(value) = value {
+        check_value(field, &value)?;
+        args.push(format!("{option}={value}"));
+    }
+    Ok(())
+}
+
+/// Appends `--depth=N` if a depth is given.
+fn push_depth(args: &mut Vec<String>, depth: Option<u32>) -> Result<(), String> {
+    match depth {
+        Some(0) => Err("'depth' must be greater than zero".to_string()),
+        Some(depth) => {
+            args.push(format!("--depth={depth}"));
+            Ok(())
+        }
+        None => Ok(()),
+    }
+}
+
+/// Appends a positional argument.
+fn push_positional(args: &mut Vec<String>, field: &str, value: String) -> Result<(), String> {
+    check_value(field, &value)?;
+    args.push(value);
+    Ok(())
+}
+
+/// Appends the remote followed by refs, which git only accepts after a remote.
+fn push_remote_and_refs(
+    args: &mut Vec<String>,
+    remote: Option<String>,
+    refs_field: &str,
+    refs: Vec<String>,
+) -> Result<(), String> {
+    let Some(remote) = remote else {
+        if refs.is_empty() {
+            return Ok(());
+        }
+        return Err(format!("'{refs_field}' requires 'remote'"));
+    };
+
+    push_positional(args, "remote", remote)?;
+    for value in refs {
+        push_positional(args, refs_field, value)?;
+    }
+    Ok(())
+}
+
+/// Rejects values git would read as options, or that are empty.
+fn check_value(field: &str, value: &str) -> Result<(), String> {
+    if value.is_empty() {
+        return Err(format!("'{field}' must not be empty"));
+    }
+    if value.starts_with('-') {
+        return Err(format!("'{field}' must not start with '-': {value}"));
+    }
+    Ok(())
+}
+
+/// Returns an object schema that rejects unknown properties.
+fn object_schema(properties: &Value, required: &[&str]) -> Value {
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false
+    })
+}
+
+/// Returns a string property schema.
+fn string_property(description: &str) -> Value {
+    json!({ "type": "string", "description": description })
+}
+
+/// Returns a boolean property schema.
+fn bool_property(description: &str) -> Value {
+    json!({ "type": "boolean", "description": description })
+}
+
+/// Returns the schema of a list of refs or patterns.
+fn refspecs_property(description: &str) -> Value {
+    json!({ "type": "array", "items": { "type": "string" }, "description": description })
+}
+
+/// Returns the schema of the `remote` property.
+fn remote_property() -> Value {
+    string_property("Remote name or URL (defaults to the current branch's remote)")
+}
+
+/// Returns the schema of the `depth` property.
+fn depth_property() -> Value {
+    json!({
+        "type": "integer",
+        "minimum": 1,
+        "description": "Limit history to this many commits"
+    })
+}
+
+/// Returns the schema of the `cwd` property.
+fn cwd_property() -> Value {
+    string_property("Absolute working directory for the Git command (optional)")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invoke(tool: GitTool, arguments: &Value) -> Result<ToolInvocation, String> {
+        tool.invocation(arguments)
+    }
+
+    #[test]
+    fn tools_are_found_by_name() {
+        for tool in GitTool::ALL {
+            assert_eq!(GitTool::from_name(tool.name()), Some(tool));
+        }
+        assert_eq!(GitTool::from_name("git_status"), None);
+    }
+
+    #[test]
+    fn schemas_list_every_parsed_field() {
+        for tool in GitTool::ALL {
+            let schema = tool.input_schema();
+            assert_eq!(schema["type"], "object", "{}", tool.name());
+            assert!(schema["properties"].is_object(), "{}", tool.name());
+        }
+    }
+
+    #[test]
+    fn clone_puts_options_before_positionals() {
+        let invocation = invoke(
+            GitTool::Clone,
+            &json!({
+                "url": "https://github.com/user/repo.git",
+                "directory": "repo",
+                "branch": "dev",
+                "depth": 1,
+                "single_branch": true,
+                "cwd": "/tmp"
+            }),
+        )
+        .unwrap();
+
+        assert_eq!(invocation.command, "clone");
+        assert_eq!(
+            invocation.args,
+            vec![
+                "--branch=dev",
+                "--depth=1",
+                "--single-branch",
+                "https://github.com/user/repo.git",
+                "repo"
+            ]
+        );
+        assert_eq!(invocation.working_dir, Some(PathBuf::from("/tmp")));
+    }
+
+    #[test]
+    fn clone_requires_url() {
+        let error = invoke(GitTool::Clone, &json!({ "directory": "repo" })).unwrap_err();
+        assert!(error.contains("url"), "{error}");
+    }
+
+    #[test]
+    fn fetch_with_remote_and_refspecs() {
+        let invocation = invoke(
+            GitTool::Fetch,
+            &json!({ "remote": "origin", "refspecs": ["main", "dev"], "prune": true }),
+        )
+        .unwrap();
+
+        assert_eq!(invocation.args, vec!["--prune", "origin", "main", "dev"]);
+    }
+
+    #[test]
+    fn fetch_rejects_contradictions() {
+        assert!(invoke(GitTool::Fetch, &json!({ "all": true, "remote": "origin" })).is_err());
+        assert!(invoke(GitTool::Fetch, &json!({ "refspecs": ["main"] })).is_err());
+        assert!(invoke(GitTool::Fetch, &json!({ "depth": 0 })).is_err());
+    }
+
+    #[test]
+    fn pull_branch_requires_remote() {
+        let error = invoke(GitTool::Pull, &json!({ "branch": "main" })).unwrap_err();
+        assert_eq!(error, "'branch' requires 'remote'");
+
+        let invocation = invoke(
+            GitTool::Pull,
+            &json!({ "remote": "origin", "branch": "main", "ff_only": true }),
+        )
+        .unwrap();
+        assert_eq!(invocation.args, vec!["--ff-only", "origin", "main"]);
+    }
+
+    #[test]
+    fn push_flags() {
+        let invocation = invoke(
+            GitTool::Push,
+            &json!({
+                "remote": "origin",
+                "refspecs": ["feature"],
+                "set_upstream": true,
+                "force_with_lease": true
+            }),
+        )
+        .unwrap();
+
+        assert_eq!(
+            invocation.args,
+            vec!["--set-upstream", "--force-with-lease", "origin", "feature"]
+        );
+    }
+
+    #[test]
+    fn values_that_look_like_options_are_rejected() {
+        let error = invoke(
+            GitTool::Push,
+            &json!({ "remote": "origin", "refspecs": ["--force"] }),
+        )
+        .unwrap_err();
+        assert_eq!(error, "'refspecs' must not start with '-': --force");
+
+        assert!(invoke(GitTool::Clone, &json!({ "url": "--upload-pack=evil" })).is_err());
+        assert!(invoke(GitTool::Clone, &json!({ "url": "" })).is_err());
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        let error = invoke(GitTool::Push, &json!({ "force": true })).unwrap_err();
+        assert!(error.contains("unknown field"), "{error}");
+    }
+
+    #[test]
+    fn ls_remote_without_arguments() {
+        let invocation = invoke(GitTool::LsRemote, &Value::Null).unwrap();
+        assert_eq!(invocation.command, "ls-remote");
+        assert_eq!(invocation.args, Vec::<String>::new());
+        assert_eq!(invocation.ref_listing, Some(RefFilter::default()));
+    }
+
+    #[test]
+    fn ls_remote_with_symref_and_filter() {
+        let invocation = invoke(
+            GitTool::LsRemote,
+            &json!({
+                "remote": "origin",
+                "symref": true,
+                "filter": ["v1.*"],
+                "kinds": ["tag"]
+            }),
+        )
+        .unwrap();
+
+        assert_eq!(invocation.args, vec!["--symref", "origin"]);
+        let filter = invocation.ref_listing.unwrap();
+        assert!(filter.matches("refs/tags/v1.2", RefKind::Tag));
+        assert!(!filter.matches("refs/heads/v1.2", RefKind::Branch));
+    }
+
+    #[test]
+    fn ls_remote_rejects_invalid_filter() {
+        let error = invoke(GitTool::LsRemote, &json!({ "filter": ["["] })).unwrap_err();
+        assert!(error.starts_with("Invalid 'filter' pattern"), "{error}");
+
+        let error = invoke(GitTool::LsRemote, &json!({ "kinds": ["branches"] })).unwrap_err();
+        assert!(error.contains("unknown variant"), "{error}");
+    }
+
+    #[test]
+    fn generic_tool_passes_arguments_through() {
+        let invocation = invoke(
+            GitTool::Generic,
+            &json!({ "command": "push", "args": ["origin", "main"], "cwd": "/work" }),
+        )
+        .unwrap();
+
+        assert_eq!(invocation.command, "push");
+        assert_eq!(invocation.args, vec!["origin", "main"]);
+        assert_eq!(invocation.working_dir, Some(PathBuf::from("/work")));
+
+        assert!(invoke(GitTool::Generic, &json!({ "args": [] })).is_err());
+    }
+}
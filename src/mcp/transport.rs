@@ -16,18 +16,19 @@
 
 use std::io;
 
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::sync::Mutex;
 
-use crate::mcp::protocol::{JsonRpcError, JsonRpcResponse};
+use crate::mcp::protocol::{JsonRpcError, JsonRpcNotification, JsonRpcResponse};
 
 /// A stdio-based MCP transport.
 ///
 /// Handles reading JSON-RPC messages from stdin and writing responses to stdout.
 pub struct StdioTransport {
-    /// Buffered reader for stdin.
-    reader: BufReader<tokio::io::Stdin>,
-    /// Handle for stdout.
-    writer: tokio::io::Stdout,
+    /// Line reader for stdin.
+    reader: Mutex<Lines<BufReader<tokio::io::Stdin>>>,
+    /// Handle for stdout, locked so each message is written whole.
+    writer: Mutex<tokio::io::Stdout>,
 }
 
 impl StdioTransport {
@@ -35,8 +36,8 @@ impl StdioTransport {
     #[must_use]
     pub fn new() -> Self {
         Self {
-            reader: BufReader::new(tokio::io::stdin()),
-            writer: tokio::io::stdout(),
+            reader: Mutex::new(BufReader::new(tokio::io::stdin()).lines()),
+            writer: Mutex::new(tokio::io::stdout()),
         }
     }
 
@@ -44,27 +45,16 @@ impl StdioTransport {
     ///
     /// Returns `None` if stdin is closed (EOF).
     ///
+    /// This method is cancellation safe: if the future is dropped before it
+    /// completes (e.g., in `tokio::select!`), no input is lost.
+    ///
     /// # Errors
     ///
     /// Returns an error if reading from stdin fails.
-    pub async fn read_line(&mut self) -> io::Result<Option<String>> {
-        let mut line = String::new();
-        let bytes_read = self.reader.read_line(&mut line).await?;
-
-        if bytes_read == 0 {
-            // EOF - stdin closed
-            return Ok(None);
-        }
-
-        // Remove the trailing newline
-        if line.ends_with('\n') {
-            line.pop();
-            if line.ends_with('\r') {
-                line.pop();
-            }
-        }
-
-        Ok(Some(line))
+    pub async fn read_line(&self) -> io::Result<Option<String>> {
+        // `Lines` strips the trailing "\n" or "\r\n" and keeps partial input
+        // buffered across cancellation
+        self.reader.lock().await.next_line().await
     }
 
     /// Writes a JSON-RPC response to stdout.
@@ -74,7 +64,7 @@ impl StdioTransport {
     /// # Errors
     ///
     /// Returns an error if serialisation or writing fails.
-    pub async fn write_response(&mut self, response: &JsonRpcResponse) -> io::Result<()> {
+    pub async fn write_response(&self, response: &JsonRpcResponse) -> io::Result<()> {
         let json = serde_json::to_string(response)
             .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
 
@@ -86,40 +76,55 @@ impl StdioTransport {
     /// # Errors
     ///
     /// Returns an error if serialisation or writing fails.
-    pub async fn write_error(&mut self, error: &JsonRpcError) -> io::Result<()> {
+    pub async fn write_error(&self, error: &JsonRpcError) -> io::Result<()> {
         let json = serde_json::to_string(error)
             .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
 
         self.write_raw(&json).await
     }
 
+    /// Writes a JSON-RPC notification to stdout.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialisation or writing fails.
+    pub async fn write_notification(&self, notification: &JsonRpcNotification) -> io::Result<()> {
+        let json = serde_json::to_string(notification)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        self.write_raw(&json).await
+    }
+
     /// Writes a raw JSON string to stdout with newline termination.
     ///
     /// # Errors
     ///
     /// Returns an error if writing fails.
-    async fn write_raw(&mut self, json: &str) -> io::Result<()> {
+    async fn write_raw(&self, json: &str) -> io::Result<()> {
         // MCP spec: messages must not contain embedded newlines
         debug_assert!(
             !json.contains('\n'),
             "JSON message must not contain embedded newlines"
         );
 
-        self.writer.write_all(json.as_bytes()).await?;
-        self.writer.write_all(b"\n").await?;
-        self.writer.flush().await?;
+        let mut writer = self.writer.lock().await;
+        writer.write_all(json.as_bytes()).await?;
+        writer.write_all(b"\n").await?;
+        writer.flush().await?;
+        drop(writer);
 
         Ok(())
     }
 
     /// Writes an arbitrary JSON value to stdout.
     ///
-    /// Used for sending messages that don't fit the standard response types.
+    /// Used for sending messages that don't fit the standard response types,
+    /// such as server-to-client notifications.
     ///
     /// # Errors
     ///
     /// Returns an error if serialisation or writing fails.
-    pub async fn write_json(&mut self, value: &serde_json::Value) -> io::Result<()> {
+    pub async fn write_json(&self, value: &serde_json::Value) -> io::Result<()> {
         let json = serde_json::to_string(value)
             .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
 
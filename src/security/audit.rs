@@ -14,7 +14,12 @@
 //! - `outcome`: Success, failure, or blocked
 //! - `reason`: Reason for blocking (if blocked)
 //! - `duration_ms`: Execution time in milliseconds (if executed)
+//!
+//! Fields taken from the request (command, arguments, working directory and
+//! reason) may carry credentials, e.g. a clone URL with an embedded token.
+//! [`AuditLogger`] runs them through an [`OutputSanitiser`] before writing.
 
+use std::borrow::Cow;
 use std::fs::{File, OpenOptions};
 use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
@@ -23,6 +28,8 @@ use std::time::{Duration, SystemTime};
 
 use serde::Serialize;
 
+use crate::git::sanitiser::OutputSanitiser;
+
 /// Outcome of a Git operation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "snake_case")]
@@ -35,6 +42,9 @@ pub enum AuditOutcome {
 
     /// Command was blocked by security policy.
     Blocked,
+
+    /// Command was cancelled by the client before it finished.
+    Cancelled,
 }
 
 /// Type of audit event.
@@ -50,6 +60,9 @@ pub enum AuditEventType {
     /// Rate limit was exceeded.
     RateLimitExceeded,
 
+    /// A running Git command was cancelled.
+    CommandCancelled,
+
     /// Server started.
     ServerStarted,
 
@@ -203,6 +216,30 @@ impl AuditEvent {
         }
     }
 
+    /// Creates an event for a command cancelled by the client.
+    #[must_use]
+    pub fn command_cancelled(
+        command: impl Into<String>,
+        args: Vec<String>,
+        working_dir: Option<PathBuf>,
+        duration: Duration,
+        reason: Option<String>,
+    ) -> Self {
+        Self {
+            timestamp: Self::current_timestamp(),
+            event_type: AuditEventType::CommandCancelled,
+            command: Some(command.into()),
+            args: Some(args),
+            working_dir,
+            outcome: AuditOutcome::Cancelled,
+            reason,
+            #[allow(clippy::cast_possible_truncation)] // Duration in ms fits in u64
+            duration_ms: Some(duration.as_millis() as u64),
+            exit_code: None,
+            shutdown_reason: None,
+        }
+    }
+
     /// Creates an event for server start.
     #[must_use]
     pub fn server_started() -> Self {
@@ -217,6 +254,29 @@ impl AuditEvent {
         event
     }
 
+    /// Returns a copy with credentials redacted from every field taken from
+    /// the request.
+    #[must_use]
+    pub fn sanitised(&self, sanitiser: &OutputSanitiser) -> Self {
+        let sanitise = |text: &String| sanitiser.sanitise(text).into_owned();
+
+        Self {
+            command: self.command.as_ref().map(sanitise),
+            args: self
+                .args
+                .as_ref()
+                .map(|args| args.iter().map(sanitise).collect()),
+            working_dir: self.working_dir.as_ref().map(|dir| {
+                match sanitiser.sanitise(&dir.to_string_lossy()) {
+                    Cow::Borrowed(_) => dir.clone(),
+                    Cow::Owned(redacted) => PathBuf::from(redacted),
+                }
+            }),
+            reason: self.reason.as_ref().map(sanitise),
+            ..self.clone()
+        }
+    }
+
     /// Gets the current timestamp in ISO 8601 format.
     fn current_timestamp() -> String {
         let now = SystemTime::now();
@@ -294,6 +354,9 @@ pub struct AuditLogger {
 
     /// Whether logging is enabled.
     enabled: bool,
+
+    /// Sanitiser applied to every event before it is written.
+    sanitiser: OutputSanitiser,
 }
 
 impl AuditLogger {
@@ -331,6 +394,7 @@ impl AuditLogger {
             log_path,
             writer: Mutex::new(Some(writer)),
             enabled: true,
+            sanitiser: OutputSanitiser::new(),
         })
     }
 
@@ -341,9 +405,17 @@ impl AuditLogger {
             log_path: PathBuf::new(),
             writer: Mutex::new(None),
             enabled: false,
+            sanitiser: OutputSanitiser::new(),
         }
     }
 
+    /// Replaces the sanitiser applied to events before they are written.
+    #[must_use]
+    pub fn with_sanitiser(mut self, sanitiser: OutputSanitiser) -> Self {
+        self.sanitiser = sanitiser;
+        self
+    }
+
     /// Returns whether logging is enabled.
     #[must_use]
     pub const fn is_enabled(&self) -> bool {
@@ -358,6 +430,8 @@ impl AuditLogger {
 
     /// Logs an audit event.
     ///
+    /// Credentials are redacted from the event before it is serialised.
+    ///
     /// # Errors
     ///
     /// Returns an error if writing to the log file fails.
@@ -367,8 +441,9 @@ impl AuditLogger {
             return Ok(());
         }
 
+        let event = event.sanitised(&self.sanitiser);
         let json =
-            serde_json::to_string(event).map_err(|e| AuditLoggerError::SerializationError {
+            serde_json::to_string(&event).map_err(|e| AuditLoggerError::SerializationError {
                 message: e.to_string(),
             })?;
 
@@ -480,6 +555,26 @@ mod tests {
         assert_eq!(event.outcome, AuditOutcome::Blocked);
     }
 
+    #[test]
+    fn audit_event_cancelled() {
+        let event = AuditEvent::command_cancelled(
+            "push",
+            vec!["origin".to_string()],
+            None,
+            Duration::from_millis(250),
+            Some("User requested cancellation".to_string()),
+        );
+
+        assert_eq!(event.event_type, AuditEventType::CommandCancelled);
+        assert_eq!(event.outcome, AuditOutcome::Cancelled);
+        assert_eq!(event.duration_ms, Some(250));
+        assert!(event.exit_code.is_none());
+
+        let json = serde_json::to_string(&event).unwrap();
+        assert!(json.contains("\"event_type\":\"command_cancelled\""));
+        assert!(json.contains("\"outcome\":\"cancelled\""));
+    }
+
     #[test]
     fn audit_event_serialization() {
         let event = AuditEvent::command_success("status", vec![], None, Duration::from_secs(1), 0);
@@ -4,10 +4,16 @@
 //!
 //! - **Branch guards**: Prevent operations on protected branches
 //! - **Push guards**: Block force pushes
+//! - **Tag guards**: Protect tags from deletion, force-updates and creation
 //! - **Repository filters**: Allow/block specific repositories
 
 use std::collections::HashSet;
 
+use glob::{Pattern, PatternError};
+
+use crate::git::transport::{transport_of, DEFAULT_ALLOWED_PROTOCOLS};
+use crate::git::{ParsedInvocation, RemoteUrl};
+
 /// Result of a security check.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SecurityCheckResult {
@@ -60,97 +66,367 @@ pub trait SecurityGuard {
     fn check(&self, command: &str, args: &[String]) -> SecurityCheckResult;
 }
 
+/// A glob pattern, compiled once, with the text it was written as.
+///
+/// Full glob syntax is supported: `*` and `**` match any characters
+/// (including `/`), `?` matches one character and `[...]` a character class.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlobPattern {
+    /// The pattern as configured.
+    source: String,
+
+    /// The compiled pattern.
+    pattern: Pattern,
+}
+
+impl GlobPattern {
+    /// Compiles a glob pattern.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is not a valid glob.
+    pub fn new(source: impl Into<String>) -> Result<Self, PatternError> {
+        let source = source.into();
+        let pattern = Pattern::new(&source)?;
+        Ok(Self { source, pattern })
+    }
+
+    /// Compiles a pattern, falling back to matching it literally if it is
+    /// not a valid glob.
+    ///
+    /// Configuration validation rejects invalid patterns, so the fallback
+    /// only applies to patterns added programmatically.
+    fn new_or_literal(source: String, compiled: Result<Pattern, PatternError>) -> Self {
+        let pattern = compiled.unwrap_or_else(|e| {
+            tracing::warn!(pattern = %source, error = %e.msg, "Invalid glob pattern; matching literally");
+            Pattern::new(&Pattern::escape(&source)).unwrap_or_default()
+        });
+        Self { source, pattern }
+    }
+
+    /// Returns the pattern as configured.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns whether a string matches the pattern.
+    #[must_use]
+    pub fn matches(&self, text: &str) -> bool {
+        self.pattern.matches(text)
+    }
+}
+
+/// Compiles patterns, dropping duplicates but keeping configuration order.
+fn compile_patterns(patterns: impl IntoIterator<Item = impl Into<String>>) -> Vec<GlobPattern> {
+    let mut compiled: Vec<GlobPattern> = Vec::new();
+    for source in patterns {
+        add_pattern(&mut compiled, source.into(), str::to_owned);
+    }
+    compiled
+}
+
+/// Adds a pattern to a list unless it is already present, compiling the
+/// glob from the source as rewritten by `normalise`.
+fn add_pattern(patterns: &mut Vec<GlobPattern>, source: String, normalise: fn(&str) -> String) {
+    if !patterns.iter().any(|p| p.source == source) {
+        let compiled = Pattern::new(&normalise(&source));
+        patterns.push(GlobPattern::new_or_literal(source, compiled));
+    }
+}
+
+/// Returns the first pattern that a globbed ref name (e.g., `release/*`)
+/// matches, i.e. a protected name or pattern the glob may cover.
+fn first_covered<'a>(glob: &str, patterns: &'a [GlobPattern]) -> Option<&'a str> {
+    let glob = Pattern::new(glob).ok()?;
+    patterns
+        .iter()
+        .find(|pattern| glob.matches(&pattern.source))
+        .map(GlobPattern::as_str)
+}
+
+/// A ref, or glob of refs, updated by a push.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PushTarget {
+    /// A branch (`refs/heads/<name>`), or a glob of branches.
+    Branch(String),
+    /// A tag (`refs/tags/<name>`), or a glob of tags.
+    Tag(String),
+    /// A short name git resolves against local refs: a branch or a tag.
+    Unqualified(String),
+    /// Any other ref (e.g., `refs/notes/commits`).
+    Other(String),
+    /// The current branch (`HEAD`, or a push without refspecs), which cannot
+    /// be known from the command line alone.
+    CurrentBranch,
+    /// Every branch (`--all`, `--branches`, `--mirror`).
+    AllBranches,
+    /// Every branch that exists under the same name on the remote (the
+    /// matching refspec `:`).
+    MatchingBranches,
+    /// Every tag (`--tags`, `--follow-tags`, `--mirror`).
+    AllTags,
+}
+
+impl PushTarget {
+    /// Classifies a destination ref, using the source to qualify short names.
+    fn of(dst: &str, src: Option<&str>) -> Self {
+        if dst == "HEAD" || dst == "@" {
+            return Self::CurrentBranch;
+        }
+        if let Some(branch) = dst.strip_prefix("refs/heads/") {
+            return Self::Branch(branch.to_string());
+        }
+        if let Some(tag) = dst.strip_prefix("refs/tags/") {
+            return Self::Tag(tag.to_string());
+        }
+        if dst.starts_with("refs/") {
+            return Self::Other(dst.to_string());
+        }
+
+        // A short destination takes the kind of a qualified source
+        match src {
+            Some(src) if src.starts_with("refs/heads/") => Self::Branch(dst.to_string()),
+            Some(src) if src.starts_with("refs/tags/") => Self::Tag(dst.to_string()),
+            _ => Self::Unqualified(dst.to_string()),
+        }
+    }
+
+    /// Returns the branch name or glob this target may update, if it names
+    /// one.
+    #[must_use]
+    pub fn branch(&self) -> Option<&str> {
+        match self {
+            Self::Branch(name) | Self::Unqualified(name) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+/// A push refspec: `[+]<src>[:<dst>]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Refspec {
+    /// Whether the refspec starts with `+` (force update).
+    pub force: bool,
+    /// The local ref or object pushed; `None` for deletes (`:<dst>`).
+    pub src: Option<String>,
+    /// The remote ref updated; the source when no destination is given.
+    pub dst: String,
+}
+
+impl Refspec {
+    /// Parses a push refspec.
+    #[must_use]
+    pub fn parse(refspec: &str) -> Self {
+        let (force, refspec) = refspec
+            .strip_prefix('+')
+            .map_or((false, refspec), |rest| (true, rest));
+        let (src, dst) = match refspec.split_once(':') {
+            Some((src, dst)) => (src, dst),
+            None => (refspec, refspec),
+        };
+        Self {
+            force,
+            src: (!src.is_empty()).then(|| src.to_string()),
+            dst: dst.to_string(),
+        }
+    }
+
+    /// Returns whether the refspec deletes its destination.
+    #[must_use]
+    pub fn is_delete(&self) -> bool {
+        self.src.is_none() && !self.is_matching()
+    }
+
+    /// Returns whether this is the matching refspec (`:` or `+:`), which
+    /// pushes every branch that exists on both sides.
+    #[must_use]
+    pub fn is_matching(&self) -> bool {
+        self.src.is_none() && self.dst.is_empty()
+    }
+}
+
+/// A ref update a push performs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefUpdate {
+    /// The ref or refs updated.
+    pub target: PushTarget,
+    /// Whether the update may be non-fast-forward.
+    pub force: bool,
+    /// Whether the update may delete the ref.
+    pub delete: bool,
+}
+
+/// Lists every ref update a `git push` command line may perform.
+///
+/// Handles `+` force prefixes, `:<dst>` and `--delete` deletes, the matching
+/// refspec `:`, `tag <name>`, `--all`, `--branches`, `--tags`, `--follow-tags`, `--mirror` and
+/// `--prune`. A push without refspecs
+/// or bulk options updates the current branch.
+#[must_use]
+pub fn push_updates(invocation: &ParsedInvocation) -> Vec<RefUpdate> {
+    let mirror = invocation.has_flag(&["--mirror"]);
+    let force = mirror || invocation.has_flag(&["-f", "--force", "--force-with-lease"]);
+    let delete = invocation.has_flag(&["-d", "--delete"]);
+    let prune = mirror || invocation.has_flag(&["--prune"]);
+
+    let mut updates = Vec::new();
+    let mut refspecs = invocation.refspecs().iter();
+    while let Some(refspec) = refspecs.next() {
+        // `tag <name>` is short for `refs/tags/<name>:refs/tags/<name>`, or
+        // `:refs/tags/<name>` with `--delete`
+        let refspec = if refspec == "tag" {
+            refspecs.next().map_or_else(
+                || Refspec::parse(refspec),
+                |name| Refspec::parse(&format!("refs/tags/{name}")),
+            )
+        } else {
+            Refspec::parse(refspec)
+        };
+        if refspec.is_matching() {
+            updates.push(RefUpdate {
+                target: PushTarget::MatchingBranches,
+                force: force || refspec.force,
+                delete: false,
+            });
+            continue;
+        }
+        let is_glob = refspec.dst.contains('*');
+        updates.push(RefUpdate {
+            target: PushTarget::of(&refspec.dst, refspec.src.as_deref()),
+            force: force || refspec.force,
+            delete: delete || refspec.is_delete() || (prune && is_glob),
+        });
+    }
+
+    if mirror || invocation.has_flag(&["--all", "--branches"]) {
+        updates.push(RefUpdate {
+            target: PushTarget::AllBranches,
+            force,
+            delete: prune,
+        });
+    }
+    if mirror || invocation.has_flag(&["--tags", "--follow-tags"]) {
+        updates.push(RefUpdate {
+            target: PushTarget::AllTags,
+            force,
+            delete: prune,
+        });
+    }
+    if updates.is_empty() {
+        updates.push(RefUpdate {
+            target: PushTarget::CurrentBranch,
+            force,
+            delete: false,
+        });
+    }
+
+    updates
+}
+
+/// Branches protected when no `protected_branches` are configured.
+pub const DEFAULT_PROTECTED_BRANCHES: &[&str] = &["main", "master", "develop"];
+
 /// Guard that protects specific branches from modifications.
 #[derive(Debug, Clone)]
 pub struct BranchGuard {
-    /// Set of protected branch names.
-    protected_branches: HashSet<String>,
+    /// Protected branch names and glob patterns, in configuration order.
+    protected_branches: Vec<GlobPattern>,
 }
 
 impl BranchGuard {
-    /// Creates a new branch guard with the given protected branches.
+    /// Creates a new branch guard with the given protected branch names or
+    /// glob patterns (e.g., "release/*").
     #[must_use]
     pub fn new(protected_branches: impl IntoIterator<Item = impl Into<String>>) -> Self {
         Self {
-            protected_branches: protected_branches.into_iter().map(Into::into).collect(),
+            protected_branches: compile_patterns(protected_branches),
         }
     }
 
     /// Creates a branch guard with common default protections.
     ///
-    /// Protected by default: `main`, `master`, `develop`, `release/*`
+    /// Protected by default: see [`DEFAULT_PROTECTED_BRANCHES`].
     #[must_use]
     pub fn with_defaults() -> Self {
-        Self::new(["main", "master", "develop"])
+        Self::new(DEFAULT_PROTECTED_BRANCHES.iter().copied())
     }
 
-    /// Adds a branch to the protected set.
+    /// Adds a branch name or pattern to the protected set.
     pub fn protect(&mut self, branch: impl Into<String>) {
-        self.protected_branches.insert(branch.into());
+        add_pattern(&mut self.protected_branches, branch.into(), str::to_owned);
     }
 
-    /// Removes a branch from the protected set.
+    /// Removes a branch name or pattern from the protected set.
     pub fn unprotect(&mut self, branch: &str) {
-        self.protected_branches.remove(branch);
+        self.protected_branches
+            .retain(|pattern| pattern.source != branch);
     }
 
     /// Checks if a branch is protected.
     #[must_use]
     pub fn is_protected(&self, branch: &str) -> bool {
-        // Direct match
-        if self.protected_branches.contains(branch) {
-            return true;
-        }
+        self.matching_pattern(branch).is_some()
+    }
 
-        // Check for wildcard patterns (e.g., "release/*")
-        for pattern in &self.protected_branches {
-            if pattern.ends_with('*') {
-                let prefix = &pattern[..pattern.len() - 1];
-                if branch.starts_with(prefix) {
-                    return true;
-                }
-            }
-        }
+    /// Returns the protected branch name or pattern that matches a branch.
+    #[must_use]
+    pub fn matching_pattern(&self, branch: &str) -> Option<&str> {
+        // Prefer an exact name over a pattern that also matches
+        self.protected_branches
+            .iter()
+            .find(|pattern| pattern.source == branch)
+            .or_else(|| {
+                self.protected_branches
+                    .iter()
+                    .find(|pattern| pattern.matches(branch))
+            })
+            .map(GlobPattern::as_str)
+    }
 
-        false
+    /// Describes why a branch is protected, for inclusion in block reasons.
+    ///
+    /// Returns an empty string for exact matches and ` (matches 'pattern')`
+    /// when the branch is covered by a wildcard.
+    fn protection_note(&self, branch: &str) -> String {
+        match self.matching_pattern(branch) {
+            Some(pattern) if pattern != branch => format!(" (matches '{pattern}')"),
+            _ => String::new(),
+        }
     }
 
-    /// Extracts branch name from command arguments.
-    fn extract_branch_from_args(command: &str, args: &[String]) -> Option<String> {
-        match command {
-            "push" => {
-                // push [remote] [refspec]
-                // refspec can be: branch, local:remote, +local:remote
-                for arg in args {
-                    if arg.starts_with('-') {
-                        continue;
-                    }
-                    // Check if this looks like a refspec
-                    if arg.contains(':') {
-                        // local:remote format
-                        let parts: Vec<&str> = arg.split(':').collect();
-                        if parts.len() == 2 {
-                            let remote_ref = parts[1].trim_start_matches('+');
-                            return Some(remote_ref.to_string());
-                        }
-                    }
-                    // else: Might be a branch name (not a URL or remote)
-                    // Skip the remote name (first non-flag arg)
-                }
-                // Check the last non-flag argument
-                args.iter().filter(|a| !a.starts_with('-')).nth(1).cloned()
+    /// Returns a protected branch a push target may update, if any.
+    ///
+    /// Globbed targets (e.g., `refs/heads/*`) hit every protected branch they
+    /// match. Bulk and current-branch targets hit any protected branch, since
+    /// the branches they update cannot be known from the command line.
+    fn protected_target(&self, target: &PushTarget) -> Option<String> {
+        let any_protected = || {
+            self.protected_branches
+                .first()
+                .map(|pattern| pattern.source.clone())
+        };
+        match target {
+            PushTarget::AllBranches | PushTarget::MatchingBranches | PushTarget::CurrentBranch => {
+                any_protected()
             }
-            "checkout" | "branch" => {
-                // Get the first non-flag argument
-                args.iter().find(|a| !a.starts_with('-')).cloned()
+            PushTarget::Branch(name) | PushTarget::Unqualified(name) if name.contains('*') => {
+                first_covered(name, &self.protected_branches)
+                    .or_else(|| self.matching_pattern(name))
+                    .map(String::from)
             }
-            "merge" | "rebase" => {
-                // Target branch is typically first non-flag arg
-                args.iter().find(|a| !a.starts_with('-')).cloned()
+            PushTarget::Branch(name) | PushTarget::Unqualified(name) => {
+                self.is_protected(name).then(|| name.clone())
             }
-            _ => None,
+            PushTarget::Tag(_) | PushTarget::Other(_) | PushTarget::AllTags => None,
         }
     }
+
+    /// Returns the branch a command operates on, for commands other than
+    /// `push`.
+    fn target_branch(invocation: &ParsedInvocation) -> Option<&str> {
+        invocation.refspecs().first().map(String::as_str)
+    }
 }
 
 impl Default for BranchGuard {
@@ -168,17 +444,18 @@ impl SecurityGuard for BranchGuard {
             return SecurityCheckResult::Allowed;
         }
 
+        let invocation = ParsedInvocation::parse(command, args);
+
         // For branch command, check for deletion (-d, -D, --delete)
         if command == "branch" {
-            let is_delete = args
-                .iter()
-                .any(|a| a == "-d" || a == "-D" || a == "--delete" || a.starts_with("--delete"));
-
-            if is_delete {
-                if let Some(branch) = Self::extract_branch_from_args(command, args) {
-                    if self.is_protected(&branch) {
+            if invocation.has_flag(&["-d", "-D", "--delete"]) {
+                if let Some(branch) = Self::target_branch(&invocation) {
+                    if self.is_protected(branch) {
                         return SecurityCheckResult::Blocked {
-                            reason: format!("Cannot delete protected branch '{branch}'"),
+                            reason: format!(
+                                "Cannot delete protected branch '{branch}'{}",
+                                self.protection_note(branch)
+                            ),
                         };
                     }
                 }
@@ -186,19 +463,43 @@ impl SecurityGuard for BranchGuard {
             return SecurityCheckResult::Allowed;
         }
 
-        // For push, check the target branch
+        // For push, check every ref the push may rewrite or delete
         if command == "push" {
-            if let Some(branch) = Self::extract_branch_from_args(command, args) {
-                // Check for force push to protected branch
-                let is_force = args
-                    .iter()
-                    .any(|a| a == "-f" || a == "--force" || a == "--force-with-lease");
-
-                if is_force && self.is_protected(&branch) {
-                    return SecurityCheckResult::Blocked {
-                        reason: format!("Cannot force push to protected branch '{branch}'"),
-                    };
+            for update in push_updates(&invocation) {
+                if !update.force && !update.delete {
+                    continue;
                 }
+                let Some(branch) = self.protected_target(&update.target) else {
+                    continue;
+                };
+                let action = if update.delete {
+                    "delete"
+                } else {
+                    "force push to"
+                };
+                let reason = match update.target {
+                    PushTarget::AllBranches => format!(
+                        "Cannot {action} all branches: includes protected branch '{branch}'"
+                    ),
+                    PushTarget::MatchingBranches => format!(
+                        "Cannot {action} matching branches: may include protected branch \
+                         '{branch}'"
+                    ),
+                    PushTarget::CurrentBranch => format!(
+                        "Cannot {action} the current branch: it may be protected (e.g., \
+                         '{branch}'); name the destination branch explicitly"
+                    ),
+                    PushTarget::Branch(ref name) | PushTarget::Unqualified(ref name)
+                        if name.contains('*') =>
+                    {
+                        format!("Cannot {action} '{name}': includes protected branch '{branch}'")
+                    }
+                    _ => format!(
+                        "Cannot {action} protected branch '{branch}'{}",
+                        self.protection_note(&branch)
+                    ),
+                };
+                return SecurityCheckResult::Blocked { reason };
             }
         }
 
@@ -260,15 +561,10 @@ impl SecurityGuard for PushGuard {
             return SecurityCheckResult::Allowed;
         }
 
-        // Check for force push flags
-        let is_force = args.iter().any(|a| {
-            a == "-f"
-                || a == "--force"
-                || a == "--force-with-lease"
-                || a.starts_with("--force-with-lease=")
-        });
-
-        if !is_force {
+        // Check for forced updates (flags, `+` refspecs or --mirror)
+        let updates = push_updates(&ParsedInvocation::parse(command, args));
+        let mut forced = updates.iter().filter(|update| update.force).peekable();
+        if forced.peek().is_none() {
             return SecurityCheckResult::Allowed;
         }
 
@@ -277,17 +573,13 @@ impl SecurityGuard for PushGuard {
             return SecurityCheckResult::Allowed;
         }
 
-        // Check if force push is allowed for this specific branch
-        let branch = args
-            .iter()
-            .filter(|a| !a.starts_with('-'))
-            .nth(1) // Skip remote name
-            .map(String::as_str);
-
-        if let Some(branch) = branch {
-            if self.force_push_allowed_branches.contains(branch) {
-                return SecurityCheckResult::Allowed;
-            }
+        // Check if force push is allowed for every branch being forced
+        if forced.all(|update| {
+            update.target.branch().is_some_and(|branch| {
+                !branch.contains('*') && self.force_push_allowed_branches.contains(branch)
+            })
+        }) {
+            return SecurityCheckResult::Allowed;
         }
 
         SecurityCheckResult::Blocked {
@@ -298,14 +590,257 @@ impl SecurityGuard for PushGuard {
     }
 }
 
+/// Guard that protects tags on remotes.
+///
+/// Tags matching a protected pattern cannot be deleted or force-updated.
+/// Independently, deleting, force-updating or creating any tag can be
+/// forbidden. Short names (e.g., `git push origin v1.0`) are treated as tags
+/// when they match a protected pattern. Otherwise they may still be tags, so
+/// an update that a `forbid_*` switch covers must name its ref in full (e.g.,
+/// `refs/heads/v1.0`).
+#[derive(Debug, Clone, Default)]
+#[allow(clippy::struct_excessive_bools)] // independent policy switches
+pub struct TagGuard {
+    /// Protected tag names and glob patterns (e.g., "v*"), in configuration
+    /// order.
+    protected_tags: Vec<GlobPattern>,
+
+    /// Whether pushing new tags is forbidden.
+    forbid_creation: bool,
+
+    /// Whether deleting tags is forbidden.
+    forbid_deletion: bool,
+
+    /// Whether force-updating tags is forbidden.
+    forbid_force_update: bool,
+}
+
+impl TagGuard {
+    /// Creates a tag guard protecting the given tag names or glob patterns.
+    #[must_use]
+    pub fn new(protected_tags: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            protected_tags: compile_patterns(protected_tags),
+            ..Self::default()
+        }
+    }
+
+    /// Sets whether pushing new tags is forbidden.
+    #[must_use]
+    pub const fn with_forbid_creation(mut self, forbid: bool) -> Self {
+        self.forbid_creation = forbid;
+        self
+    }
+
+    /// Sets whether deleting tags is forbidden.
+    #[must_use]
+    pub const fn with_forbid_deletion(mut self, forbid: bool) -> Self {
+        self.forbid_deletion = forbid;
+        self
+    }
+
+    /// Sets whether force-updating tags is forbidden.
+    #[must_use]
+    pub const fn with_forbid_force_update(mut self, forbid: bool) -> Self {
+        self.forbid_force_update = forbid;
+        self
+    }
+
+    /// Returns the protected tag name or pattern that matches a tag.
+    #[must_use]
+    pub fn matching_pattern(&self, tag: &str) -> Option<&str> {
+        self.protected_tags
+            .iter()
+            .find(|pattern| pattern.source == tag)
+            .or_else(|| {
+                self.protected_tags
+                    .iter()
+                    .find(|pattern| pattern.matches(tag))
+            })
+            .map(GlobPattern::as_str)
+    }
+
+    /// Returns the protected pattern a tag, glob of tags or all tags may
+    /// touch, if any.
+    fn protected_match(&self, tag: Option<&str>) -> Option<&str> {
+        tag.map_or_else(
+            || self.protected_tags.first().map(GlobPattern::as_str),
+            |tag| {
+                self.matching_pattern(tag)
+                    .or_else(|| first_covered(tag, &self.protected_tags))
+            },
+        )
+    }
+
+    /// Returns the action an update performs on a tag, whether it is
+    /// forbidden for every tag, and the rule that forbids it.
+    const fn rule(&self, update: &RefUpdate) -> (&'static str, bool, &'static str) {
+        if update.delete {
+            (
+                "delete",
+                self.forbid_deletion,
+                "tag deletion is not allowed",
+            )
+        } else if update.force {
+            (
+                "force-update",
+                self.forbid_force_update,
+                "tag force-updates are not allowed",
+            )
+        } else {
+            ("push", self.forbid_creation, "tag creation is not allowed")
+        }
+    }
+
+    /// Checks a single tag update.
+    ///
+    /// `tag` is `None` when the update covers every tag.
+    fn check_update(&self, tag: Option<&str>, update: &RefUpdate) -> SecurityCheckResult {
+        let subject = tag.map_or_else(|| "all tags".to_string(), |tag| format!("tag '{tag}'"));
+        let (action, forbidden, rule) = self.rule(update);
+
+        if forbidden {
+            return SecurityCheckResult::Blocked {
+                reason: format!("Cannot {action} {subject}: {rule}"),
+            };
+        }
+
+        if update.delete || update.force {
+            if let Some(pattern) = self.protected_match(tag) {
+                let reason = match tag {
+                    Some(tag) if tag == pattern => {
+                        format!("Cannot {action} protected tag '{tag}'")
+                    }
+                    Some(tag) if self.matching_pattern(tag).is_some() => {
+                        format!("Cannot {action} protected tag '{tag}' (matches '{pattern}')")
+                    }
+                    _ => format!("Cannot {action} {subject}: includes protected tag '{pattern}'"),
+                };
+                return SecurityCheckResult::Blocked { reason };
+            }
+        }
+
+        SecurityCheckResult::Allowed
+    }
+}
+
+impl SecurityGuard for TagGuard {
+    fn check(&self, command: &str, args: &[String]) -> SecurityCheckResult {
+        if command != "push" {
+            return SecurityCheckResult::Allowed;
+        }
+
+        for update in push_updates(&ParsedInvocation::parse(command, args)) {
+            let tag = match &update.target {
+                PushTarget::Tag(tag) => Some(tag.as_str()),
+                PushTarget::Unqualified(name) if self.matching_pattern(name).is_some() => {
+                    Some(name.as_str())
+                }
+                PushTarget::Unqualified(name) => {
+                    // Git resolves a short name to a tag if one exists locally
+                    let (action, forbidden, rule) = self.rule(&update);
+                    if forbidden {
+                        return SecurityCheckResult::Blocked {
+                            reason: format!(
+                                "Cannot {action} '{name}': it may be a tag and {rule}; name \
+                                 the ref in full (e.g., 'refs/heads/{name}')"
+                            ),
+                        };
+                    }
+                    continue;
+                }
+                PushTarget::AllTags => None,
+                _ => continue,
+            };
+
+            let result = self.check_update(tag, &update);
+            if result.is_blocked() {
+                return result;
+            }
+        }
+
+        SecurityCheckResult::Allowed
+    }
+}
+
+/// Guard that restricts the transports remote URLs may use.
+///
+/// Only listed transports (e.g., "https", "ssh") are allowed; everything
+/// else, including `ext::` and `fd::` remote helpers and local paths
+/// (`file`), is blocked. Remote names such as "origin" are not URLs and are
+/// left to git's own `GIT_ALLOW_PROTOCOL` check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransportGuard {
+    /// Allowed transport names, sorted.
+    allowed: Vec<String>,
+}
+
+impl TransportGuard {
+    /// Creates a transport guard allowing the given transports.
+    #[must_use]
+    pub fn new(allowed: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        let mut allowed: Vec<String> = allowed
+            .into_iter()
+            .map(|protocol| protocol.into().to_ascii_lowercase())
+            .collect();
+        allowed.sort();
+        allowed.dedup();
+        Self { allowed }
+    }
+
+    /// Creates a transport guard allowing the default transports
+    /// (https, ssh).
+    #[must_use]
+    pub fn with_defaults() -> Self {
+        Self::new(DEFAULT_ALLOWED_PROTOCOLS.iter().copied())
+    }
+
+    /// Returns the allowed transport names, sorted.
+    #[must_use]
+    pub fn allowed(&self) -> &[String] {
+        &self.allowed
+    }
+
+    /// Returns whether a transport is allowed.
+    #[must_use]
+    pub fn is_allowed(&self, transport: &str) -> bool {
+        self.allowed.iter().any(|allowed| allowed == transport)
+    }
+}
+
+impl Default for TransportGuard {
+    fn default() -> Self {
+        Self::with_defaults()
+    }
+}
+
+impl SecurityGuard for TransportGuard {
+    fn check(&self, command: &str, args: &[String]) -> SecurityCheckResult {
+        let invocation = ParsedInvocation::parse(command, args);
+        let Some(url) = invocation.remote() else {
+            return SecurityCheckResult::Allowed;
+        };
+
+        match transport_of(url) {
+            Some(transport) if !self.is_allowed(&transport) => SecurityCheckResult::Blocked {
+                reason: format!(
+                    "Transport '{transport}' is not allowed for '{url}' (allowed: {})",
+                    self.allowed.join(", ")
+                ),
+            },
+            _ => SecurityCheckResult::Allowed,
+        }
+    }
+}
+
 /// Filter that controls which repositories can be accessed.
 #[derive(Debug, Clone)]
 pub struct RepoFilter {
-    /// Allowlist of repository patterns.
-    allowlist: HashSet<String>,
+    /// Allowlist of repository patterns, compiled in normalised form.
+    allowlist: Vec<GlobPattern>,
 
-    /// Blocklist of repository patterns.
-    blocklist: HashSet<String>,
+    /// Blocklist of repository patterns, compiled in normalised form.
+    blocklist: Vec<GlobPattern>,
 
     /// Whether to use allowlist mode (only allow listed repos).
     allowlist_mode: bool,
@@ -314,54 +849,103 @@ pub struct RepoFilter {
 impl RepoFilter {
     /// Creates a new repository filter.
     #[must_use]
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         Self {
-            allowlist: HashSet::new(),
-            blocklist: HashSet::new(),
+            allowlist: Vec::new(),
+            blocklist: Vec::new(),
             allowlist_mode: false,
         }
     }
 
     /// Creates a filter that allows all repositories except those in the blocklist.
     #[must_use]
-    pub fn blocklist_mode() -> Self {
+    pub const fn blocklist_mode() -> Self {
         Self {
-            allowlist: HashSet::new(),
-            blocklist: HashSet::new(),
+            allowlist: Vec::new(),
+            blocklist: Vec::new(),
             allowlist_mode: false,
         }
     }
 
     /// Creates a filter that only allows repositories in the allowlist.
     #[must_use]
-    pub fn allowlist_mode() -> Self {
+    pub const fn allowlist_mode() -> Self {
         Self {
-            allowlist: HashSet::new(),
-            blocklist: HashSet::new(),
+            allowlist: Vec::new(),
+            blocklist: Vec::new(),
             allowlist_mode: true,
         }
     }
 
     /// Adds a repository pattern to the allowlist.
     pub fn allow(&mut self, pattern: impl Into<String>) {
-        self.allowlist.insert(pattern.into());
+        add_pattern(&mut self.allowlist, pattern.into(), Self::normalise_url);
     }
 
     /// Adds a repository pattern to the blocklist.
     pub fn block(&mut self, pattern: impl Into<String>) {
-        self.blocklist.insert(pattern.into());
+        add_pattern(&mut self.blocklist, pattern.into(), Self::normalise_url);
+    }
+
+    /// Compiles a repository pattern the way the filter matches it: as a
+    /// glob over the normalised URL (see [`Self::normalise_url`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the normalised pattern is not a valid glob.
+    pub fn compile_pattern(pattern: &str) -> Result<GlobPattern, PatternError> {
+        let pattern_glob = Pattern::new(&Self::normalise_url(pattern))?;
+        Ok(GlobPattern {
+            source: pattern.to_string(),
+            pattern: pattern_glob,
+        })
+    }
+
+    /// Returns whether the filter can block anything.
+    #[must_use]
+    pub fn has_rules(&self) -> bool {
+        self.allowlist_mode || !self.blocklist.is_empty()
+    }
+
+    /// Checks the URLs a command will contact, after remote names and
+    /// `pushurl`/`insteadOf` rewrites are resolved (see
+    /// [`resolve_remote_urls`](crate::git::resolve_remote_urls)).
+    #[must_use]
+    pub fn check_remote_urls(&self, urls: &[RemoteUrl]) -> SecurityCheckResult {
+        for RemoteUrl { remote, url } in urls {
+            if let Err(rule) = self.evaluate(url) {
+                let reason = if remote == url {
+                    format!("Repository '{url}' is not allowed by policy ({rule})")
+                } else {
+                    format!("Remote '{remote}' resolves to '{url}', which is not allowed by policy ({rule})")
+                };
+                return SecurityCheckResult::Blocked { reason };
+            }
+        }
+
+        SecurityCheckResult::Allowed
     }
 
     /// Checks if a repository URL is allowed.
     #[must_use]
     pub fn is_allowed(&self, repo_url: &str) -> bool {
+        self.evaluate(repo_url).is_ok()
+    }
+
+    /// Evaluates a repository URL against the allow/block lists.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the rule that rejected the URL: the matching
+    /// blocklist pattern, or the fact that no allowlist pattern matched.
+    pub fn evaluate(&self, repo_url: &str) -> Result<(), String> {
         // Normalise the URL for comparison
         let normalised = Self::normalise_url(repo_url);
 
         // Check blocklist first (always applies)
         for pattern in &self.blocklist {
             if Self::matches_pattern(&normalised, pattern) {
-                return false;
+                return Err(format!("matches blocklist pattern '{}'", pattern.as_str()));
             }
         }
 
@@ -369,18 +953,23 @@ impl RepoFilter {
         if self.allowlist_mode {
             for pattern in &self.allowlist {
                 if Self::matches_pattern(&normalised, pattern) {
-                    return true;
+                    return Ok(());
                 }
             }
-            return false;
+            return Err("no allowlist pattern matches".to_string());
         }
 
         // In blocklist mode, anything not blocked is allowed
-        true
+        Ok(())
     }
 
     /// Normalises a repository URL for comparison.
-    fn normalise_url(url: &str) -> String {
+    ///
+    /// Lowercases the URL and strips the scheme, credentials, `.git` suffix
+    /// and trailing slash, so that `https://github.com/Org/Repo.git` and
+    /// `git@github.com:org/repo` both become `github.com/org/repo`.
+    #[must_use]
+    pub fn normalise_url(url: &str) -> String {
         let mut normalised = url.to_lowercase();
 
         // Remove protocol
@@ -415,57 +1004,31 @@ impl RepoFilter {
         normalised
     }
 
-    /// Checks if a URL matches a pattern.
-    fn matches_pattern(url: &str, pattern: &str) -> bool {
-        let normalised_pattern = Self::normalise_url(pattern);
-
-        // Exact match
-        if url == normalised_pattern {
-            return true;
-        }
-
-        // Wildcard matching
-        if normalised_pattern.contains('*') {
-            let parts: Vec<&str> = normalised_pattern.split('*').collect();
-
-            if parts.len() == 2 {
-                let prefix = parts[0];
-                let suffix = parts[1];
-
-                return url.starts_with(prefix) && url.ends_with(suffix);
-            }
-        }
-
-        // Prefix matching (e.g., "github.com/org" matches "github.com/org/repo")
-        if url.starts_with(&normalised_pattern) {
-            let remainder = &url[normalised_pattern.len()..];
-            return remainder.is_empty() || remainder.starts_with('/');
-        }
-
-        false
+    /// Checks if a normalised URL matches a pattern.
+    ///
+    /// A pattern also matches every repository below a path it matches, so
+    /// "github.com/org" matches "github.com/org/repo".
+    fn matches_pattern(url: &str, pattern: &GlobPattern) -> bool {
+        pattern.matches(url)
+            || url
+                .match_indices('/')
+                .any(|(index, _)| pattern.matches(&url[..index]))
     }
 
     /// Extracts repository URL from command arguments.
     fn extract_repo_url(command: &str, args: &[String]) -> Option<String> {
-        match command {
-            "clone" => {
-                // clone <url> [directory]
-                args.first().cloned()
-            }
-            "push" | "pull" | "fetch" | "ls-remote" => {
-                // First non-flag argument might be the remote
-                args.iter().find(|a| !a.starts_with('-')).cloned()
-            }
-            "remote" => {
-                // remote add <name> <url>
-                if args.first().map(String::as_str) == Some("add") {
-                    args.get(2).cloned()
-                } else {
-                    None
-                }
-            }
-            _ => None,
+        if command == "remote" {
+            // remote add [<options>] <name> <url>
+            let invocation = ParsedInvocation::parse(command, args);
+            return match invocation.refspecs() {
+                [action, _, url, ..] if action == "add" => Some(url.clone()),
+                _ => None,
+            };
         }
+
+        ParsedInvocation::parse(command, args)
+            .remote()
+            .map(String::from)
     }
 }
 
@@ -485,14 +1048,14 @@ impl SecurityGuard for RepoFilter {
         }
 
         if let Some(repo_url) = Self::extract_repo_url(command, args) {
-            // Skip checking for remote names like "origin"
+            // Remote names like "origin" are checked once resolved to URLs
             if !repo_url.contains('/') && !repo_url.contains('.') {
                 return SecurityCheckResult::Allowed;
             }
 
-            if !self.is_allowed(&repo_url) {
+            if let Err(rule) = self.evaluate(&repo_url) {
                 return SecurityCheckResult::Blocked {
-                    reason: format!("Repository '{repo_url}' is not allowed by policy"),
+                    reason: format!("Repository '{repo_url}' is not allowed by policy ({rule})"),
                 };
             }
         }
@@ -527,6 +1090,47 @@ mod tests {
         assert!(!guard.is_protected("releases/1.0"));
     }
 
+    #[test]
+    fn branch_guard_full_glob_syntax() {
+        let guard = BranchGuard::new(["release/**", "hotfix-?", "v[0-9]*.x", "team/*/main"]);
+
+        assert!(guard.is_protected("release/1.0/rc"));
+        assert!(guard.is_protected("hotfix-1"));
+        assert!(!guard.is_protected("hotfix-12"));
+        assert!(guard.is_protected("v2.x"));
+        assert!(!guard.is_protected("vnext.x"));
+        assert!(guard.is_protected("team/web/main"));
+        assert!(!guard.is_protected("team/web/dev"));
+    }
+
+    #[test]
+    fn invalid_pattern_matches_literally() {
+        let guard = BranchGuard::new(["release/[1-"]);
+
+        assert!(guard.is_protected("release/[1-"));
+        assert!(!guard.is_protected("release/1"));
+        assert!(GlobPattern::new("release/[1-").is_err());
+    }
+
+    #[test]
+    fn branch_guard_reports_matching_pattern() {
+        let guard = BranchGuard::new(["main", "release/*"]);
+
+        assert_eq!(guard.matching_pattern("main"), Some("main"));
+        assert_eq!(guard.matching_pattern("release/1.0"), Some("release/*"));
+        assert_eq!(guard.matching_pattern("feature"), None);
+
+        let result = guard.check(
+            "push",
+            &[
+                "--force".to_string(),
+                "origin".to_string(),
+                "release/1.0".to_string(),
+            ],
+        );
+        assert!(result.reason().unwrap().contains("(matches 'release/*')"));
+    }
+
     #[test]
     fn branch_guard_blocks_delete() {
         let guard = BranchGuard::with_defaults();
@@ -608,6 +1212,411 @@ mod tests {
         assert!(result.is_blocked());
     }
 
+    #[test]
+    fn push_option_value_is_not_mistaken_for_remote() {
+        let guard = BranchGuard::with_defaults();
+        let args: Vec<String> = ["-o", "ci.skip", "--force", "origin", "main"]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        assert!(guard.check("push", &args).is_blocked());
+        assert!(PushGuard::block_force_push()
+            .check("push", &args)
+            .is_blocked());
+    }
+
+    #[test]
+    fn branch_guard_checks_every_pushed_branch() {
+        let guard = BranchGuard::with_defaults();
+        let args: Vec<String> = ["-f", "origin", "feature", "HEAD:main"]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        let result = guard.check("push", &args);
+        assert_eq!(
+            result.reason(),
+            Some("Cannot force push to protected branch 'main'")
+        );
+    }
+
+    #[test]
+    fn push_guard_detects_bundled_force_flag() {
+        let guard = PushGuard::block_force_push();
+        let args: Vec<String> = ["-fu", "origin", "feature"]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        assert!(guard.check("push", &args).is_blocked());
+    }
+
+    // Refspec tests
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    fn updates(push_args: &[&str]) -> Vec<RefUpdate> {
+        push_updates(&ParsedInvocation::parse("push", &args(push_args)))
+    }
+
+    #[test]
+    fn refspec_parses_force_and_delete() {
+        assert_eq!(
+            Refspec::parse("+feature:main"),
+            Refspec {
+                force: true,
+                src: Some("feature".to_string()),
+                dst: "main".to_string(),
+            }
+        );
+        assert_eq!(Refspec::parse("main").dst, "main");
+        assert!(Refspec::parse(":main").is_delete());
+        assert!(!Refspec::parse("main").is_delete());
+        assert!(Refspec::parse(":").is_matching());
+        assert!(Refspec::parse("+:").is_matching());
+        assert!(!Refspec::parse(":").is_delete());
+    }
+
+    #[test]
+    fn push_targets_are_classified() {
+        let targets: Vec<PushTarget> = updates(&[
+            "origin",
+            "main",
+            "refs/heads/dev",
+            "refs/tags/v1.0",
+            "refs/tags/v2.0:v2",
+            "HEAD",
+            "refs/notes/commits",
+        ])
+        .into_iter()
+        .map(|update| update.target)
+        .collect();
+
+        assert_eq!(
+            targets,
+            vec![
+                PushTarget::Unqualified("main".to_string()),
+                PushTarget::Branch("dev".to_string()),
+                PushTarget::Tag("v1.0".to_string()),
+                PushTarget::Tag("v2".to_string()),
+                PushTarget::CurrentBranch,
+                PushTarget::Other("refs/notes/commits".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn push_without_refspecs_updates_current_branch() {
+        assert_eq!(
+            updates(&["-f", "origin"]),
+            vec![RefUpdate {
+                target: PushTarget::CurrentBranch,
+                force: true,
+                delete: false,
+            }]
+        );
+        assert_eq!(
+            updates(&["--tags", "origin"])[0].target,
+            PushTarget::AllTags
+        );
+    }
+
+    #[test]
+    fn matching_refspec_updates_matching_branches() {
+        assert_eq!(
+            updates(&["origin", "+:"]),
+            vec![RefUpdate {
+                target: PushTarget::MatchingBranches,
+                force: true,
+                delete: false,
+            }]
+        );
+        assert_eq!(
+            updates(&["--force", "origin", ":"]),
+            vec![RefUpdate {
+                target: PushTarget::MatchingBranches,
+                force: true,
+                delete: false,
+            }]
+        );
+        assert!(!updates(&["origin", ":"])[0].force);
+    }
+
+    #[test]
+    fn mirror_forces_and_deletes_everything() {
+        let updates = updates(&["--mirror", "origin"]);
+
+        assert_eq!(updates.len(), 2);
+        assert!(updates.iter().all(|update| update.force && update.delete));
+    }
+
+    #[test]
+    fn branch_guard_blocks_refspec_force_and_delete() {
+        let guard = BranchGuard::with_defaults();
+        let reason = |push_args: &[&str]| {
+            guard
+                .check("push", &args(push_args))
+                .reason()
+                .map(String::from)
+        };
+
+        assert_eq!(
+            reason(&["origin", "+main"]).as_deref(),
+            Some("Cannot force push to protected branch 'main'")
+        );
+        assert_eq!(
+            reason(&["origin", ":main"]).as_deref(),
+            Some("Cannot delete protected branch 'main'")
+        );
+        assert_eq!(
+            reason(&["--delete", "origin", "refs/heads/develop"]).as_deref(),
+            Some("Cannot delete protected branch 'develop'")
+        );
+        assert_eq!(
+            reason(&["-f", "origin", "refs/heads/*:refs/heads/*"]).as_deref(),
+            Some("Cannot force push to '*': includes protected branch 'main'")
+        );
+        assert_eq!(
+            reason(&["--mirror", "origin"]).as_deref(),
+            Some("Cannot delete all branches: includes protected branch 'main'")
+        );
+        assert_eq!(
+            reason(&["origin", "+:"]).as_deref(),
+            Some("Cannot force push to matching branches: may include protected branch 'main'")
+        );
+        assert!(reason(&["--force", "origin", ":"]).is_some());
+        assert_eq!(reason(&["origin", ":"]), None);
+        assert!(reason(&["-f", "--all", "origin"]).is_some());
+        assert!(reason(&["-f", "origin", "HEAD"]).is_some());
+        assert!(reason(&["-f", "origin"]).is_some());
+    }
+
+    #[test]
+    fn branch_guard_allows_safe_pushes() {
+        let guard = BranchGuard::with_defaults();
+
+        for push_args in [
+            &["origin", "main"][..],
+            &["--all", "origin"],
+            &["-f", "origin", "HEAD:feature"],
+            &["origin", ":feature"],
+            &["-f", "origin", "refs/tags/main"],
+        ] {
+            assert!(
+                guard.check("push", &args(push_args)).is_allowed(),
+                "{push_args:?} should be allowed"
+            );
+        }
+    }
+
+    #[test]
+    fn push_guard_detects_refspec_force() {
+        let mut guard = PushGuard::block_force_push();
+        guard.allow_force_push_to("feature");
+
+        assert!(guard
+            .check("push", &args(&["origin", "+feature"]))
+            .is_allowed());
+        assert!(guard
+            .check("push", &args(&["origin", "+feature", "+other"]))
+            .is_blocked());
+        assert!(guard
+            .check("push", &args(&["--mirror", "origin"]))
+            .is_blocked());
+        assert!(guard
+            .check("push", &args(&["origin", ":feature"]))
+            .is_allowed());
+    }
+
+    // TagGuard tests
+
+    #[test]
+    fn tag_guard_protects_matching_tags() {
+        let guard = TagGuard::new(["v*"]);
+        let reason = |push_args: &[&str]| {
+            guard
+                .check("push", &args(push_args))
+                .reason()
+                .map(String::from)
+        };
+
+        assert_eq!(
+            reason(&["origin", "+refs/tags/v1.0"]).as_deref(),
+            Some("Cannot force-update protected tag 'v1.0' (matches 'v*')")
+        );
+        assert_eq!(
+            reason(&["origin", ":refs/tags/v1.0"]).as_deref(),
+            Some("Cannot delete protected tag 'v1.0' (matches 'v*')")
+        );
+        assert_eq!(
+            reason(&["--delete", "origin", "v1.0"]).as_deref(),
+            Some("Cannot delete protected tag 'v1.0' (matches 'v*')")
+        );
+        assert_eq!(
+            reason(&["--delete", "origin", "tag", "v1.0"]).as_deref(),
+            Some("Cannot delete protected tag 'v1.0' (matches 'v*')")
+        );
+        assert_eq!(
+            reason(&["-f", "--tags", "origin"]).as_deref(),
+            Some("Cannot force-update all tags: includes protected tag 'v*'")
+        );
+        assert_eq!(
+            reason(&["origin", "+refs/tags/*:refs/tags/*"]).as_deref(),
+            Some("Cannot force-update tag '*': includes protected tag 'v*'")
+        );
+        assert!(reason(&["--mirror", "origin"]).is_some());
+    }
+
+    #[test]
+    fn tag_guard_allows_unprotected_and_new_tags() {
+        let guard = TagGuard::new(["v*"]);
+
+        for push_args in [
+            &["origin", "refs/tags/v2.0"][..],
+            &["--tags", "origin"],
+            &["origin", "tag", "v2.0"],
+            &["origin", ":refs/tags/nightly"],
+            &["-f", "origin", "main"],
+        ] {
+            assert!(
+                guard.check("push", &args(push_args)).is_allowed(),
+                "{push_args:?} should be allowed"
+            );
+        }
+    }
+
+    #[test]
+    fn tag_guard_forbids_operations_on_any_tag() {
+        let guard = TagGuard::default()
+            .with_forbid_creation(true)
+            .with_forbid_deletion(true)
+            .with_forbid_force_update(true);
+        let reason = |push_args: &[&str]| {
+            guard
+                .check("push", &args(push_args))
+                .reason()
+                .map(String::from)
+        };
+
+        assert_eq!(
+            reason(&["origin", "tag", "nightly"]).as_deref(),
+            Some("Cannot push tag 'nightly': tag creation is not allowed")
+        );
+        assert_eq!(
+            reason(&["--follow-tags", "origin", "refs/heads/main"]).as_deref(),
+            Some("Cannot push all tags: tag creation is not allowed")
+        );
+        assert_eq!(
+            reason(&["origin", ":refs/tags/nightly"]).as_deref(),
+            Some("Cannot delete tag 'nightly': tag deletion is not allowed")
+        );
+        assert_eq!(
+            reason(&["origin", "+refs/tags/nightly"]).as_deref(),
+            Some("Cannot force-update tag 'nightly': tag force-updates are not allowed")
+        );
+        assert_eq!(
+            reason(&["--delete", "origin", "tag", "nightly"]).as_deref(),
+            Some("Cannot delete tag 'nightly': tag deletion is not allowed")
+        );
+        assert!(reason(&["origin", "refs/heads/main"]).is_none());
+        assert!(reason(&["origin", "HEAD:refs/heads/main"]).is_none());
+    }
+
+    #[test]
+    fn tag_guard_treats_short_names_as_possible_tags() {
+        let reason = |guard: &TagGuard, push_args: &[&str]| {
+            guard
+                .check("push", &args(push_args))
+                .reason()
+                .map(String::from)
+        };
+
+        let guard = TagGuard::default().with_forbid_deletion(true);
+        assert_eq!(
+            reason(&guard, &["-d", "origin", "v1.0"]).as_deref(),
+            Some(
+                "Cannot delete 'v1.0': it may be a tag and tag deletion is not allowed; name the \
+                 ref in full (e.g., 'refs/heads/v1.0')"
+            )
+        );
+        assert!(reason(&guard, &["origin", ":v1.0"]).is_some());
+        assert!(reason(&guard, &["-d", "origin", "refs/heads/v1.0"]).is_none());
+        assert!(reason(&guard, &["origin", "main"]).is_none());
+
+        let guard = TagGuard::default().with_forbid_creation(true);
+        assert!(reason(&guard, &["origin", "v1.0"]).is_some());
+        assert!(reason(&guard, &["origin", "refs/heads/main"]).is_none());
+        assert!(reason(&guard, &["origin"]).is_none());
+    }
+
+    // TransportGuard tests
+
+    #[test]
+    fn transport_guard_allows_https_and_ssh_by_default() {
+        let guard = TransportGuard::default();
+
+        for url in [
+            "https://github.com/org/repo.git",
+            "ssh://git@github.com/org/repo.git",
+            "git@github.com:org/repo.git",
+        ] {
+            assert!(
+                guard.check("clone", &[url.to_string()]).is_allowed(),
+                "{url}"
+            );
+        }
+        assert!(guard.check("fetch", &["origin".to_string()]).is_allowed());
+        assert!(guard.check("fetch", &[]).is_allowed());
+    }
+
+    #[test]
+    fn transport_guard_blocks_dangerous_transports() {
+        let guard = TransportGuard::default();
+
+        for (url, transport) in [
+            ("ext::sh -c touch% /tmp/pwned", "ext"),
+            ("fd::3,4", "fd"),
+            ("file:///etc", "file"),
+            ("/home/user/.ssh", "file"),
+            ("../other-repo", "file"),
+            ("http://example.com/repo.git", "http"),
+        ] {
+            let result = guard.check(
+                "clone",
+                &["--depth".to_string(), "1".to_string(), url.to_string()],
+            );
+            match result {
+                SecurityCheckResult::Blocked { reason } => {
+                    assert!(
+                        reason.contains(&format!("Transport '{transport}'")),
+                        "{reason}"
+                    );
+                    assert!(reason.contains("(allowed: https, ssh)"), "{reason}");
+                }
+                SecurityCheckResult::Allowed => panic!("{url} should be blocked"),
+            }
+        }
+
+        let result = guard.check("push", &["--repo=ext::sh".to_string(), "--all".to_string()]);
+        assert!(result.is_blocked());
+    }
+
+    #[test]
+    fn transport_guard_configured_protocols() {
+        let guard = TransportGuard::new(["HTTPS", "file", "https"]);
+
+        assert_eq!(guard.allowed(), &["file", "https"]);
+        assert!(guard
+            .check("clone", &["/srv/repo.git".to_string()])
+            .is_allowed());
+        assert!(guard
+            .check("clone", &["ssh://host/repo".to_string()])
+            .is_blocked());
+    }
+
     // RepoFilter tests
 
     #[test]
@@ -651,6 +1660,30 @@ mod tests {
         assert!(filter.is_allowed("https://github.com/other/repo.git"));
     }
 
+    #[test]
+    fn repo_filter_full_glob_syntax() {
+        let mut filter = RepoFilter::allowlist_mode();
+        filter.allow("github.com/*/infra-*");
+        filter.allow("gitlab.com/group/**/repo-?");
+        filter.allow("github.com/[ab]team/*");
+
+        assert!(filter.is_allowed("https://github.com/org/infra-dns.git"));
+        assert!(!filter.is_allowed("https://github.com/org/app.git"));
+        assert!(filter.is_allowed("https://gitlab.com/group/sub/deeper/repo-1"));
+        assert!(!filter.is_allowed("https://gitlab.com/group/sub/repo-10"));
+        assert!(filter.is_allowed("git@github.com:bteam/tool.git"));
+        assert!(!filter.is_allowed("git@github.com:cteam/tool.git"));
+    }
+
+    #[test]
+    fn repo_filter_prefix_pattern_matches_nested_repos() {
+        let mut filter = RepoFilter::blocklist_mode();
+        filter.block("github.com/org");
+
+        assert!(!filter.is_allowed("https://github.com/org/repo.git"));
+        assert!(filter.is_allowed("https://github.com/organisation/repo.git"));
+    }
+
     #[test]
     fn repo_filter_blocks_clone() {
         let mut filter = RepoFilter::blocklist_mode();
@@ -669,6 +1702,35 @@ mod tests {
         assert!(result.is_allowed());
     }
 
+    #[test]
+    fn repo_filter_skips_option_values() {
+        let mut filter = RepoFilter::blocklist_mode();
+        filter.block("github.com/blocked/repo");
+
+        let args: Vec<String> = ["--depth", "1", "https://github.com/blocked/repo.git"]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert!(filter.check("clone", &args).is_blocked());
+    }
+
+    #[test]
+    fn repo_filter_reports_matching_rule() {
+        let mut filter = RepoFilter::allowlist_mode();
+        filter.allow("github.com/myorg/*");
+        filter.block("github.com/myorg/secret");
+
+        assert_eq!(
+            filter.evaluate("https://github.com/myorg/secret.git"),
+            Err("matches blocklist pattern 'github.com/myorg/secret'".to_string())
+        );
+        assert_eq!(
+            filter.evaluate("https://github.com/other/repo.git"),
+            Err("no allowlist pattern matches".to_string())
+        );
+        assert_eq!(filter.evaluate("https://github.com/myorg/repo.git"), Ok(()));
+    }
+
     #[test]
     fn repo_filter_allows_origin() {
         let filter = RepoFilter::allowlist_mode();
@@ -678,6 +1740,33 @@ mod tests {
         assert!(result.is_allowed());
     }
 
+    #[test]
+    fn repo_filter_checks_resolved_remote_urls() {
+        let mut filter = RepoFilter::allowlist_mode();
+        filter.allow("github.com/myorg/*");
+        let remote_url = |remote: &str, url: &str| RemoteUrl {
+            remote: remote.to_string(),
+            url: url.to_string(),
+        };
+
+        let allowed = [remote_url("origin", "https://github.com/myorg/repo.git")];
+        assert!(filter.check_remote_urls(&allowed).is_allowed());
+
+        let urls = [
+            remote_url("origin", "https://github.com/myorg/repo.git"),
+            remote_url("origin", "https://evil.example/repo.git"),
+        ];
+        assert_eq!(
+            filter.check_remote_urls(&urls).reason(),
+            Some(
+                "Remote 'origin' resolves to 'https://evil.example/repo.git', \
+                 which is not allowed by policy (no allowlist pattern matches)"
+            )
+        );
+        assert!(!RepoFilter::new().has_rules());
+        assert!(filter.has_rules());
+    }
+
     // SecurityCheckResult tests
 
     #[test]
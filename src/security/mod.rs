@@ -6,7 +6,9 @@
 //! - **Protected branches**: Prevents operations on protected branches
 //! - **Force push blocking**: Prevents force pushes (unless explicitly allowed)
 //! - **Repository allowlist/blocklist**: Controls which repositories can be accessed
-//! - **Rate limiting**: Prevents runaway AI operations
+//! - **Transport allowlist**: Blocks `ext::`, `fd::`, local paths and other unlisted transports
+//! - **Rate limiting**: Prevents runaway AI operations (per repository)
+//! - **Workspace roots**: Confines working directories and clone targets
 //!
 //! # Security Model
 //!
@@ -32,7 +34,12 @@
 pub mod audit;
 pub mod guards;
 pub mod rate_limit;
+pub mod workspace;
 
 pub use audit::{AuditEvent, AuditLogger, ShutdownReason};
-pub use guards::{BranchGuard, PushGuard, RepoFilter, SecurityGuard};
-pub use rate_limit::RateLimiter;
+pub use guards::{
+    push_updates, BranchGuard, GlobPattern, PushGuard, PushTarget, RefUpdate, Refspec, RepoFilter,
+    SecurityCheckResult, SecurityGuard, TagGuard, TransportGuard,
+};
+pub use rate_limit::{CommandRateLimiter, RateLimited, RateLimiter, RepoRateLimiter};
+pub use workspace::WorkspaceSandbox;
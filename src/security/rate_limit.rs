@@ -11,6 +11,15 @@
 //! - Tokens are replenished at `refill_rate` per second
 //! - If no tokens available, operation is blocked
 //!
+//! [`CommandRateLimiter`] layers per-command buckets on top of this, so that
+//! e.g. `push` can be throttled harder than `ls-remote`.
+//!
+//! [`RepoRateLimiter`] keeps a separate set of buckets per repository
+//! (keyed by normalised remote URL), held in a bounded LRU, so a runaway loop
+//! against one remote cannot starve work on every other remote. A
+//! session-wide bucket bounds the total across repositories, so switching
+//! remotes cannot get around the limit either.
+//!
 //! # Mutex Poisoning
 //!
 //! This module handles mutex poisoning gracefully. If a thread panics while
@@ -18,6 +27,7 @@
 //! value from the poisoned mutex. For a rate limiter, having potentially stale
 //! state is preferable to crashing the entire application.
 
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Mutex, MutexGuard};
 use std::time::{Duration, Instant};
@@ -87,6 +97,12 @@ impl RateLimiter {
         Self::new(u64::MAX, f64::MAX)
     }
 
+    /// Creates a full, unused rate limiter with the same burst and refill settings.
+    #[must_use]
+    pub fn fresh(&self) -> Self {
+        Self::new(self.max_burst, self.refill_rate)
+    }
+
     /// Locks the tokens mutex, recovering from poison if necessary.
     ///
     /// If the mutex is poisoned (a thread panicked while holding the lock),
@@ -178,6 +194,9 @@ impl RateLimiter {
 
         if current_tokens >= 1.0 {
             Duration::ZERO
+        } else if self.refill_rate <= 0.0 {
+            // Tokens are never replenished
+            Duration::MAX
         } else {
             let tokens_needed = 1.0 - current_tokens;
             let seconds = tokens_needed / self.refill_rate;
@@ -185,6 +204,32 @@ impl RateLimiter {
         }
     }
 
+    /// Returns time until the bucket is full again, at which point it is
+    /// indistinguishable from a fresh one.
+    ///
+    /// Returns `Duration::ZERO` if the bucket is full.
+    ///
+    /// # Mutex Poisoning
+    ///
+    /// If a mutex is poisoned, this method recovers gracefully.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // max_burst as f64 is acceptable
+    pub fn time_until_full(&self) -> Duration {
+        self.refill();
+
+        let tokens = self.lock_tokens();
+        let tokens_needed = self.max_burst as f64 - *tokens;
+        drop(tokens);
+
+        if tokens_needed <= 0.0 {
+            Duration::ZERO
+        } else if self.refill_rate <= 0.0 {
+            Duration::MAX
+        } else {
+            Duration::try_from_secs_f64(tokens_needed / self.refill_rate).unwrap_or(Duration::MAX)
+        }
+    }
+
     /// Refills tokens based on elapsed time.
     #[allow(clippy::significant_drop_tightening)] // Lock ordering is intentional
     #[allow(clippy::cast_precision_loss)] // max_burst as f64 is acceptable
@@ -247,6 +292,289 @@ impl Default for RateLimiter {
     }
 }
 
+/// Rate limiter with optional per-command buckets.
+///
+/// Commands with an override draw exclusively from their own bucket; all
+/// other commands share the default bucket.
+#[derive(Debug)]
+pub struct CommandRateLimiter {
+    /// Bucket shared by commands without an override.
+    default: RateLimiter,
+
+    /// Dedicated buckets keyed by Git subcommand.
+    overrides: HashMap<String, RateLimiter>,
+}
+
+impl CommandRateLimiter {
+    /// Creates a command rate limiter with the given default bucket and no overrides.
+    #[must_use]
+    pub fn new(default: RateLimiter) -> Self {
+        Self {
+            default,
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// Creates a command rate limiter that allows unlimited operations.
+    #[must_use]
+    pub fn unlimited() -> Self {
+        Self::new(RateLimiter::unlimited())
+    }
+
+    /// Gives a command its own bucket, replacing any existing override.
+    pub fn set_override(&mut self, command: impl Into<String>, limiter: RateLimiter) {
+        self.overrides.insert(command.into(), limiter);
+    }
+
+    /// Returns the bucket that applies to a command.
+    #[must_use]
+    pub fn limiter_for(&self, command: &str) -> &RateLimiter {
+        self.overrides.get(command).unwrap_or(&self.default)
+    }
+
+    /// Attempts to acquire a token for the given command.
+    ///
+    /// Returns `true` if the operation is allowed, `false` if rate limited.
+    pub fn try_acquire(&self, command: &str) -> bool {
+        self.limiter_for(command).try_acquire()
+    }
+
+    /// Returns time until the given command can next be executed.
+    #[must_use]
+    pub fn time_until_available(&self, command: &str) -> Duration {
+        self.limiter_for(command).time_until_available()
+    }
+
+    /// Returns time until every bucket is full again.
+    #[must_use]
+    pub fn time_until_full(&self) -> Duration {
+        self.overrides
+            .values()
+            .map(RateLimiter::time_until_full)
+            .fold(self.default.time_until_full(), Duration::max)
+    }
+}
+
+impl CommandRateLimiter {
+    /// Creates a full, unused copy of this limiter with the same settings.
+    #[must_use]
+    pub fn fresh(&self) -> Self {
+        Self {
+            default: self.default.fresh(),
+            overrides: self
+                .overrides
+                .iter()
+                .map(|(command, limiter)| (command.clone(), limiter.fresh()))
+                .collect(),
+        }
+    }
+}
+
+impl Default for CommandRateLimiter {
+    fn default() -> Self {
+        Self::new(RateLimiter::default_for_ai())
+    }
+}
+
+/// Default number of repositories tracked by [`RepoRateLimiter`].
+pub const DEFAULT_MAX_TRACKED_REPOSITORIES: usize = 256;
+
+/// Default burst capacity of the session-wide bucket.
+pub const DEFAULT_SESSION_MAX_BURST: u64 = 100;
+
+/// Default refill rate of the session-wide bucket (operations per second).
+pub const DEFAULT_SESSION_REFILL_RATE: f64 = 20.0;
+
+/// A rate limit rejection for a single repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimited {
+    /// The repository key the operation was for.
+    pub key: String,
+
+    /// Whether the session-wide bucket, rather than the repository's own,
+    /// is exhausted.
+    pub session: bool,
+
+    /// Time until the next operation on this key would be allowed.
+    pub retry_after: Duration,
+}
+
+/// Buckets for one repository, with LRU bookkeeping.
+#[derive(Debug)]
+struct RepoBucket {
+    /// Per-command buckets for this repository.
+    limiter: CommandRateLimiter,
+
+    /// Logical time of the last access (higher is more recent).
+    last_used: u64,
+}
+
+/// Rate limiter with independent buckets per repository.
+///
+/// Each repository key receives a fresh copy of the template
+/// [`CommandRateLimiter`] on first use. At most `capacity` keys are
+/// tracked. When full, the least recently used key whose buckets have
+/// refilled completely is evicted, since a fresh copy is no different, or
+/// the least recently used key if none has. Every operation also takes a
+/// token from the session-wide bucket, so cycling through repository URLs
+/// cannot raise the total rate beyond it.
+#[derive(Debug)]
+pub struct RepoRateLimiter {
+    /// Template cloned for each new repository key.
+    template: CommandRateLimiter,
+
+    /// Bucket shared by every repository.
+    session: RateLimiter,
+
+    /// Buckets keyed by repository.
+    buckets: Mutex<HashMap<String, RepoBucket>>,
+
+    /// Maximum number of tracked repository keys.
+    capacity: usize,
+
+    /// Logical clock for LRU ordering.
+    clock: AtomicU64,
+}
+
+impl RepoRateLimiter {
+    /// Creates a per-repository rate limiter with an unlimited session bucket.
+    ///
+    /// # Arguments
+    ///
+    /// * `template` — Buckets given to each repository on first use
+    /// * `capacity` — Maximum number of repositories tracked at once (at least 1)
+    #[must_use]
+    pub fn new(template: CommandRateLimiter, capacity: usize) -> Self {
+        Self {
+            template,
+            session: RateLimiter::unlimited(),
+            buckets: Mutex::new(HashMap::new()),
+            capacity: capacity.max(1),
+            clock: AtomicU64::new(0),
+        }
+    }
+
+    /// Creates a per-repository rate limiter that allows unlimited operations.
+    #[must_use]
+    pub fn unlimited() -> Self {
+        Self::new(CommandRateLimiter::unlimited(), 1)
+    }
+
+    /// Sets the bucket shared by every repository.
+    #[must_use]
+    pub const fn with_session(mut self, session: RateLimiter) -> Self {
+        self.session = session;
+        self
+    }
+
+    /// Locks the bucket map, recovering from poison if necessary.
+    fn lock_buckets(&self) -> MutexGuard<'_, HashMap<String, RepoBucket>> {
+        self.buckets.lock().unwrap_or_else(|poisoned| {
+            tracing::warn!(
+                "Rate limiter buckets mutex was poisoned; recovering with potentially stale state"
+            );
+            poisoned.into_inner()
+        })
+    }
+
+    /// Returns the least recently used key whose buckets are full, or the
+    /// least recently used key if none are.
+    fn evictable(buckets: &HashMap<String, RepoBucket>) -> Option<String> {
+        buckets
+            .iter()
+            .min_by_key(|(_, bucket)| {
+                (
+                    !bucket.limiter.time_until_full().is_zero(),
+                    bucket.last_used,
+                )
+            })
+            .map(|(key, _)| key.clone())
+    }
+
+    /// Runs `f` against the buckets for `key`, creating them if needed.
+    fn with_bucket<T>(&self, key: &str, f: impl FnOnce(&CommandRateLimiter) -> T) -> T {
+        let now = self.clock.fetch_add(1, Ordering::Relaxed);
+        let mut buckets = self.lock_buckets();
+
+        if !buckets.contains_key(key) && buckets.len() >= self.capacity {
+            if let Some(oldest) = Self::evictable(&buckets) {
+                buckets.remove(&oldest);
+            }
+        }
+
+        let bucket = buckets
+            .entry(key.to_string())
+            .or_insert_with(|| RepoBucket {
+                limiter: self.template.fresh(),
+                last_used: now,
+            });
+        bucket.last_used = now;
+
+        // The session bucket is only touched while the map is locked, so
+        // both buckets are checked and drawn from together
+        let result = f(&bucket.limiter);
+        drop(buckets);
+        result
+    }
+
+    /// Attempts to acquire a token for a command against a repository.
+    ///
+    /// A token is taken from both the repository's bucket for this command
+    /// and the session-wide bucket, or from neither.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RateLimited`] with the key and retry delay if either bucket
+    /// is exhausted.
+    pub fn try_acquire(&self, key: &str, command: &str) -> Result<(), RateLimited> {
+        self.with_bucket(key, |limiter| {
+            let repository = limiter.limiter_for(command);
+            let (exhausted, session) = if !repository.would_allow() {
+                (repository, false)
+            } else if !self.session.would_allow() {
+                (&self.session, true)
+            } else {
+                repository.try_acquire();
+                self.session.try_acquire();
+                return Ok(());
+            };
+            Err(RateLimited {
+                key: key.to_string(),
+                session,
+                retry_after: exhausted.time_until_available(),
+            })
+        })
+    }
+
+    /// Returns time until a command can next be executed against a repository.
+    ///
+    /// Neither tracks the repository nor evicts another one.
+    #[must_use]
+    pub fn time_until_available(&self, key: &str, command: &str) -> Duration {
+        let buckets = self.lock_buckets();
+        let delay = buckets.get(key).map_or(Duration::ZERO, |bucket| {
+            bucket.limiter.time_until_available(command)
+        });
+        drop(buckets);
+        delay.max(self.session.time_until_available())
+    }
+
+    /// Returns the number of repositories currently tracked.
+    #[must_use]
+    pub fn tracked_repositories(&self) -> usize {
+        self.lock_buckets().len()
+    }
+}
+
+impl Default for RepoRateLimiter {
+    fn default() -> Self {
+        Self::new(
+            CommandRateLimiter::default(),
+            DEFAULT_MAX_TRACKED_REPOSITORIES,
+        )
+    }
+}
+
 /// Statistics about rate limiter usage.
 #[derive(Debug, Clone, Copy)]
 pub struct RateLimiterStats {
@@ -416,4 +744,158 @@ mod tests {
 
         assert!((stats.block_rate() - 0.0).abs() < f64::EPSILON);
     }
+
+    #[test]
+    fn time_until_available_without_refill() {
+        let limiter = RateLimiter::new(1, 0.0);
+        limiter.try_acquire();
+
+        assert_eq!(limiter.time_until_available(), Duration::MAX);
+    }
+
+    #[test]
+    fn command_rate_limiter_uses_override_bucket() {
+        let mut limiter = CommandRateLimiter::new(RateLimiter::new(5, 0.0));
+        limiter.set_override("push", RateLimiter::new(1, 0.0));
+
+        assert!(limiter.try_acquire("push"));
+        assert!(!limiter.try_acquire("push"));
+
+        // Other commands are unaffected by the exhausted push bucket
+        for _ in 0..5 {
+            assert!(limiter.try_acquire("fetch"));
+        }
+        assert!(!limiter.try_acquire("ls-remote"));
+    }
+
+    #[test]
+    fn command_rate_limiter_override_does_not_drain_default() {
+        let mut limiter = CommandRateLimiter::new(RateLimiter::new(1, 0.0));
+        limiter.set_override("fetch", RateLimiter::new(100, 0.0));
+
+        for _ in 0..50 {
+            assert!(limiter.try_acquire("fetch"));
+        }
+        assert!(limiter.try_acquire("push"));
+    }
+
+    #[test]
+    fn repo_rate_limiter_isolates_repositories() {
+        let limiter = RepoRateLimiter::new(CommandRateLimiter::new(RateLimiter::new(2, 0.0)), 8);
+
+        assert!(limiter.try_acquire("github.com/org/busy", "fetch").is_ok());
+        assert!(limiter.try_acquire("github.com/org/busy", "fetch").is_ok());
+
+        let blocked = limiter
+            .try_acquire("github.com/org/busy", "fetch")
+            .unwrap_err();
+        assert_eq!(blocked.key, "github.com/org/busy");
+        assert_eq!(blocked.retry_after, Duration::MAX);
+
+        // A different repository still has its full burst
+        assert!(limiter.try_acquire("github.com/org/quiet", "fetch").is_ok());
+        assert!(limiter.try_acquire("github.com/org/quiet", "fetch").is_ok());
+    }
+
+    #[test]
+    fn repo_rate_limiter_reports_time_until_available() {
+        let limiter = RepoRateLimiter::new(CommandRateLimiter::new(RateLimiter::new(1, 10.0)), 8);
+
+        assert_eq!(limiter.time_until_available("repo", "push"), Duration::ZERO);
+        assert!(limiter.try_acquire("repo", "push").is_ok());
+
+        let blocked = limiter.try_acquire("repo", "push").unwrap_err();
+        assert!(blocked.retry_after > Duration::ZERO);
+        assert!(blocked.retry_after.as_millis() <= 150);
+        assert_eq!(
+            limiter.time_until_available("other", "push"),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn repo_rate_limiter_evicts_least_recently_used_full_bucket() {
+        let limiter = RepoRateLimiter::new(CommandRateLimiter::new(RateLimiter::new(1, 100.0)), 2);
+
+        assert!(limiter.try_acquire("a", "fetch").is_ok());
+        assert!(limiter.try_acquire("b", "fetch").is_ok());
+        std::thread::sleep(Duration::from_millis(30));
+
+        // Both buckets have refilled; touch "a" so that "b" is evicted
+        assert!(limiter.try_acquire("a", "fetch").is_ok());
+        assert!(limiter.try_acquire("c", "fetch").is_ok());
+        assert_eq!(limiter.tracked_repositories(), 2);
+
+        // "a" kept its used bucket
+        assert!(limiter.try_acquire("a", "fetch").is_err());
+    }
+
+    #[test]
+    fn repo_rate_limiter_admits_new_keys_when_no_bucket_has_refilled() {
+        let limiter = RepoRateLimiter::new(CommandRateLimiter::new(RateLimiter::new(1, 0.0)), 2);
+
+        assert!(limiter.try_acquire("a", "fetch").is_ok());
+        assert!(limiter.try_acquire("b", "fetch").is_ok());
+        assert!(limiter.try_acquire("b", "fetch").is_err());
+
+        // Neither bucket refills, so the least recently used one is evicted
+        assert_eq!(limiter.time_until_available("c", "fetch"), Duration::ZERO);
+        assert!(limiter.try_acquire("c", "fetch").is_ok());
+        assert_eq!(limiter.tracked_repositories(), 2);
+        assert!(limiter.try_acquire("b", "fetch").is_err());
+    }
+
+    #[test]
+    fn repo_rate_limiter_cannot_be_reset_by_cycling_keys() {
+        let limiter = RepoRateLimiter::new(CommandRateLimiter::new(RateLimiter::new(1, 0.0)), 2)
+            .with_session(RateLimiter::new(3, 0.0));
+
+        for key in ["a", "b", "c"] {
+            assert!(limiter.try_acquire(key, "fetch").is_ok());
+        }
+
+        let blocked = limiter.try_acquire("d", "fetch").unwrap_err();
+        assert_eq!(blocked.key, "d");
+        assert!(blocked.session);
+        assert_eq!(blocked.retry_after, Duration::MAX);
+        assert_eq!(limiter.time_until_available("d", "fetch"), Duration::MAX);
+    }
+
+    #[test]
+    fn repo_rate_limiter_refusal_does_not_draw_from_session() {
+        let limiter = RepoRateLimiter::new(CommandRateLimiter::new(RateLimiter::new(1, 0.0)), 8)
+            .with_session(RateLimiter::new(2, 0.0));
+
+        assert!(limiter.try_acquire("a", "fetch").is_ok());
+        let blocked = limiter.try_acquire("a", "fetch").unwrap_err();
+        assert!(!blocked.session);
+
+        assert!(limiter.try_acquire("b", "fetch").is_ok());
+        assert!(limiter.try_acquire("c", "fetch").unwrap_err().session);
+    }
+
+    #[test]
+    fn repo_rate_limiter_time_until_available_has_no_side_effects() {
+        let limiter = RepoRateLimiter::new(CommandRateLimiter::new(RateLimiter::new(1, 0.0)), 1);
+
+        assert_eq!(limiter.time_until_available("a", "fetch"), Duration::ZERO);
+        assert_eq!(limiter.tracked_repositories(), 0);
+
+        assert!(limiter.try_acquire("a", "fetch").is_ok());
+        assert_eq!(limiter.time_until_available("b", "fetch"), Duration::ZERO);
+        assert_eq!(limiter.tracked_repositories(), 1);
+        assert_eq!(limiter.time_until_available("a", "fetch"), Duration::MAX);
+    }
+
+    #[test]
+    fn repo_rate_limiter_applies_command_overrides_per_repository() {
+        let mut template = CommandRateLimiter::new(RateLimiter::new(10, 0.0));
+        template.set_override("push", RateLimiter::new(1, 0.0));
+        let limiter = RepoRateLimiter::new(template, 8);
+
+        assert!(limiter.try_acquire("repo1", "push").is_ok());
+        assert!(limiter.try_acquire("repo1", "push").is_err());
+        assert!(limiter.try_acquire("repo2", "push").is_ok());
+        assert!(limiter.try_acquire("repo1", "fetch").is_ok());
+    }
 }
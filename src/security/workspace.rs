@@ -0,0 +1,252 @@
+//! Confinement of working directories to workspace roots.
+//!
+//! A tool call's working directory can be any path, so without limits an
+//! agent can pull in any checkout on the machine or clone into the user's
+//! home directory. When workspace roots are configured, the working directory
+//! and every directory a `clone` creates must lie inside one of them once
+//! symlinks are resolved, so a link inside a root cannot lead out of it.
+
+use std::path::{Path, PathBuf};
+
+use super::guards::SecurityCheckResult;
+use crate::git::clone::resolve_path;
+use crate::git::ParsedInvocation;
+
+/// Directories that commands may run in and clone into.
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceSandbox {
+    /// Canonical root directories. Empty means unrestricted.
+    roots: Vec<PathBuf>,
+}
+
+impl WorkspaceSandbox {
+    /// Creates a sandbox confined to the given roots.
+    ///
+    /// Roots are canonicalised; a root that cannot be (e.g., because it does
+    /// not exist) is kept as given. No roots means no restriction.
+    #[must_use]
+    pub fn new(roots: impl IntoIterator<Item = impl Into<PathBuf>>) -> Self {
+        let roots = roots
+            .into_iter()
+            .map(|root| {
+                let root = root.into();
+                root.canonicalize().unwrap_or(root)
+            })
+            .collect();
+        Self { roots }
+    }
+
+    /// Creates a sandbox that allows every directory.
+    #[must_use]
+    pub const fn unrestricted() -> Self {
+        Self { roots: Vec::new() }
+    }
+
+    /// Returns the canonical workspace roots.
+    #[must_use]
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Returns whether any roots are configured.
+    #[must_use]
+    pub fn is_restricted(&self) -> bool {
+        !self.roots.is_empty()
+    }
+
+    /// Returns whether a resolved path is a root or lies below one.
+    #[must_use]
+    pub fn contains(&self, resolved: &Path) -> bool {
+        !self.is_restricted() || self.roots.iter().any(|root| resolved.starts_with(root))
+    }
+
+    /// Checks the working directory of a command and, for `clone`, the
+    /// directories it creates (`<directory>` and `--separate-git-dir`).
+    ///
+    /// Without a working directory, the server's current directory is
+    /// checked, since that is where git runs.
+    #[must_use]
+    pub fn check(
+        &self,
+        command: &str,
+        args: &[String],
+        working_dir: Option<&Path>,
+    ) -> SecurityCheckResult {
+        if !self.is_restricted() {
+            return SecurityCheckResult::Allowed;
+        }
+
+        let cwd = match working_dir.map_or_else(std::env::current_dir, |dir| Ok(dir.to_path_buf()))
+        {
+            Ok(cwd) => cwd,
+            Err(e) => {
+                return SecurityCheckResult::Blocked {
+                    reason: format!("Cannot determine the working directory: {e}"),
+                }
+            }
+        };
+        let resolved_cwd = match cwd.canonicalize() {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                return SecurityCheckResult::Blocked {
+                    reason: format!(
+                        "Working directory '{}' cannot be resolved: {e}",
+                        cwd.display()
+                    ),
+                }
+            }
+        };
+        if let Some(reason) = self.outside_reason("Working directory", &cwd, &resolved_cwd) {
+            return SecurityCheckResult::Blocked { reason };
+        }
+
+        if command == "clone" {
+            let invocation = ParsedInvocation::parse(command, args);
+            let destinations = [
+                ("Clone target", invocation.target_dir()),
+                ("Clone git dir", invocation.flag_value("--separate-git-dir")),
+            ];
+            for (what, destination) in destinations {
+                let Some(destination) = destination else {
+                    continue;
+                };
+                let path = resolved_cwd.join(destination);
+                let reason = match resolve_path(&path) {
+                    Ok(resolved) => self.outside_reason(what, &path, &resolved),
+                    Err(e) => Some(format!(
+                        "{what} '{}' cannot be resolved: {e}",
+                        path.display()
+                    )),
+                };
+                if let Some(reason) = reason {
+                    return SecurityCheckResult::Blocked { reason };
+                }
+            }
+        }
+
+        SecurityCheckResult::Allowed
+    }
+
+    /// Describes why a path is outside the roots, or returns `None` if it is
+    /// inside.
+    fn outside_reason(&self, what: &str, path: &Path, resolved: &Path) -> Option<String> {
+        if self.contains(resolved) {
+            return None;
+        }
+
+        let shown = if path == resolved {
+            format!("'{}'", path.display())
+        } else {
+            format!(
+                "'{}' (resolves to '{}')",
+                path.display(),
+                resolved.display()
+            )
+        };
+        let roots: Vec<String> = self
+            .roots
+            .iter()
+            .map(|root| root.display().to_string())
+            .collect();
+        Some(format!(
+            "{what} {shown} is outside the workspace roots ({})",
+            roots.join(", ")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn unrestricted_allows_everything() {
+        let sandbox = WorkspaceSandbox::unrestricted();
+        assert!(sandbox
+            .check("pull", &[], Some(Path::new("/etc")))
+            .is_allowed());
+    }
+
+    #[test]
+    fn working_directory_must_be_inside_a_root() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let outside = tempfile::TempDir::new().unwrap();
+        let repo = workspace.path().join("repo");
+        std::fs::create_dir(&repo).unwrap();
+        let sandbox = WorkspaceSandbox::new([workspace.path()]);
+
+        assert!(sandbox.check("pull", &[], Some(&repo)).is_allowed());
+        assert!(sandbox
+            .check("pull", &[], Some(workspace.path()))
+            .is_allowed());
+
+        let result = sandbox.check("pull", &[], Some(outside.path()));
+        assert!(result
+            .reason()
+            .unwrap()
+            .contains("is outside the workspace roots"));
+
+        let missing = workspace.path().join("missing");
+        let result = sandbox.check("pull", &[], Some(&missing));
+        assert!(result.reason().unwrap().contains("cannot be resolved"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_out_of_a_root_are_blocked() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let outside = tempfile::TempDir::new().unwrap();
+        let link = workspace.path().join("link");
+        std::os::unix::fs::symlink(outside.path(), &link).unwrap();
+        let sandbox = WorkspaceSandbox::new([workspace.path()]);
+
+        let result = sandbox.check("fetch", &[], Some(&link));
+        assert!(result.reason().unwrap().contains("resolves to"));
+
+        let result = sandbox.check(
+            "clone",
+            &args(&["https://github.com/org/repo.git", "link/repo"]),
+            Some(workspace.path()),
+        );
+        assert!(result.reason().unwrap().starts_with("Clone target"));
+
+        let result = sandbox.check(
+            "clone",
+            &args(&["https://github.com/org/repo.git", "missing/../link/repo"]),
+            Some(workspace.path()),
+        );
+        assert!(result.reason().unwrap().starts_with("Clone target"));
+    }
+
+    #[test]
+    fn clone_destinations_must_be_inside_a_root() {
+        let workspace = tempfile::TempDir::new().unwrap();
+        let sandbox = WorkspaceSandbox::new([workspace.path()]);
+        let url = "https://github.com/org/repo.git";
+        let cwd = Some(workspace.path());
+
+        assert!(sandbox.check("clone", &args(&[url]), cwd).is_allowed());
+        assert!(sandbox
+            .check("clone", &args(&[url, "nested/dir"]), cwd)
+            .is_allowed());
+        assert!(sandbox
+            .check("clone", &args(&[url, "../escape"]), cwd)
+            .is_blocked());
+        assert!(sandbox
+            .check("clone", &args(&[url, "/tmp/elsewhere"]), cwd)
+            .is_blocked());
+        assert!(sandbox
+            .check(
+                "clone",
+                &args(&["--separate-git-dir", "../git", url, "dir"]),
+                cwd
+            )
+            .reason()
+            .unwrap()
+            .starts_with("Clone git dir"));
+    }
+}
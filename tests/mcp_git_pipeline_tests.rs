@@ -16,11 +16,13 @@
 
 use std::path::PathBuf;
 use std::process::Command;
+use std::time::Duration;
 
 use serde_json::{json, Value};
 use tempfile::TempDir;
 
-use git_proxy_mcp::git::executor::GitExecutor;
+use git_proxy_mcp::git::executor::{ExecutorError, GitExecutor};
+use git_proxy_mcp::git::refs::{parse_ls_remote, RefFilter, RefKind};
 use git_proxy_mcp::mcp::protocol::{parse_message, IncomingMessage, RequestId};
 use git_proxy_mcp::mcp::server::{McpServer, SecurityConfig, ToolCallResult};
 use git_proxy_mcp::security::AuditLogger;
@@ -51,6 +53,13 @@ fn create_server_with_security(config: SecurityConfig) -> McpServer {
     McpServer::new(executor, config, audit_logger)
 }
 
+/// Creates an executor that may use local repositories.
+///
+/// Local paths use the `file` transport, which git is told to refuse by default.
+fn local_executor() -> GitExecutor {
+    GitExecutor::new().with_allowed_protocols(vec!["file".to_string()])
+}
+
 /// Helper to create a temporary git repository for testing.
 fn create_temp_repo() -> Option<TempDir> {
     let temp_dir = TempDir::new().ok()?;
@@ -252,6 +261,7 @@ fn test_server_with_custom_security_config() {
         protected_branches: vec!["main".to_string(), "release/*".to_string()],
         repo_allowlist: Some(vec!["github.com/myorg/*".to_string()]),
         repo_blocklist: None,
+        ..SecurityConfig::default()
     };
 
     let server = create_server_with_security(config);
@@ -339,7 +349,7 @@ async fn test_executor_fetch_in_local_repo() {
         "Should add remote"
     );
 
-    let executor = GitExecutor::new();
+    let executor = local_executor();
 
     let command = git_proxy_mcp::git::command::GitCommand::new(
         "fetch",
@@ -361,6 +371,361 @@ async fn test_executor_fetch_in_local_repo() {
     }
 }
 
+#[tokio::test]
+async fn test_executor_filters_ls_remote_before_truncation() {
+    if !git_available() {
+        eprintln!("Skipping test: git not available");
+        return;
+    }
+
+    let Some(repo_dir) = create_temp_repo() else {
+        eprintln!("Skipping test: failed to create temp repo");
+        return;
+    };
+
+    // One commit with many lightweight tags and one release tag
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(repo_dir.path())
+            .output()
+            .expect("git should run");
+        assert!(output.status.success(), "git {args:?} failed: {output:?}");
+    };
+    git(&["commit", "--allow-empty", "-m", "initial"]);
+    for i in 0..200 {
+        git(&["tag", &format!("build-{i}")]);
+    }
+    git(&["tag", "v1.0"]);
+
+    // The full listing is far larger than the output limit
+    let executor = GitExecutor::with_limits(Duration::from_secs(30), 2000)
+        .with_allowed_protocols(vec!["file".to_string()]);
+    let command = git_proxy_mcp::git::command::GitCommand::new(
+        "ls-remote",
+        vec![repo_dir.path().to_str().unwrap().to_string()],
+        None,
+    )
+    .expect("ls-remote command should be valid");
+
+    let unfiltered = executor
+        .execute(&command)
+        .await
+        .expect("ls-remote should run");
+    assert!(unfiltered.stdout_truncated);
+
+    // Filtering happens before the limit, so the release tag is listed in full
+    let filter = RefFilter::new(&["v*".to_string()], vec![RefKind::Tag]).unwrap();
+    let filtered = executor
+        .execute_with_filter(&command, None, Some(&filter))
+        .await
+        .expect("ls-remote should run");
+    assert!(filtered.success, "ls-remote should succeed: {filtered:?}");
+    assert!(!filtered.stdout_truncated);
+
+    let refs = parse_ls_remote(&filtered.stdout);
+    assert_eq!(refs.len(), 1);
+    assert_eq!(refs[0].name, "refs/tags/v1.0");
+    assert_eq!(refs[0].kind, RefKind::Tag);
+}
+
+#[tokio::test]
+async fn test_executor_reports_clone_progress() {
+    if !git_available() {
+        eprintln!("Skipping test: git not available");
+        return;
+    }
+
+    let Some(source_dir) = create_temp_repo() else {
+        eprintln!("Skipping test: failed to create temp repo");
+        return;
+    };
+    std::fs::write(source_dir.path().join("README.md"), "# Test").unwrap();
+    for args in [&["add", "."][..], &["commit", "-m", "Initial commit"][..]] {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(source_dir.path())
+            .output()
+            .unwrap();
+        assert!(status.status.success(), "git {args:?} should succeed");
+    }
+
+    let dest_dir = TempDir::new().unwrap();
+    let source_url = format!(
+        "file://{}",
+        source_dir.path().display().to_string().replace('\\', "/")
+    );
+    let command = git_proxy_mcp::git::command::GitCommand::new(
+        "clone",
+        vec![source_url, "copy".to_string()],
+        Some(dest_dir.path().to_path_buf()),
+    )
+    .expect("clone command should be valid");
+
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    let output = local_executor()
+        .execute_with_progress(&command, Some(sender))
+        .await
+        .expect("clone should not error");
+
+    assert!(output.success, "clone should succeed: {output:?}");
+    assert!(
+        !output.stderr.contains('\r'),
+        "progress redraws should be collapsed: {:?}",
+        output.stderr
+    );
+
+    let mut updates = Vec::new();
+    while let Ok(update) = receiver.try_recv() {
+        updates.push(update);
+    }
+    assert!(!updates.is_empty(), "clone should report progress");
+    assert!(updates.iter().all(|update| update.percent <= 100));
+}
+
+#[tokio::test]
+async fn test_clone_creates_the_reported_checkout() {
+    if !git_available() {
+        eprintln!("Skipping test: git not available");
+        return;
+    }
+
+    let Some(source_dir) = create_bare_repo() else {
+        eprintln!("Skipping test: failed to create bare repo");
+        return;
+    };
+
+    let dest_dir = TempDir::new().unwrap();
+    let source_url = format!(
+        "file://{}",
+        source_dir.path().display().to_string().replace('\\', "/")
+    );
+    let mut command = git_proxy_mcp::git::command::GitCommand::new(
+        "clone",
+        vec![source_url.clone()],
+        Some(dest_dir.path().to_path_buf()),
+    )
+    .expect("clone command should be valid");
+    command
+        .resolve_clone_target()
+        .expect("clone target should be allowed");
+
+    let checkout = command
+        .clone_target()
+        .expect("clone should have a target")
+        .to_path_buf();
+    assert!(checkout.is_absolute());
+    assert!(checkout.starts_with(dest_dir.path().canonicalize().unwrap()));
+
+    let output = local_executor()
+        .execute(&command)
+        .await
+        .expect("clone should not error");
+    assert!(output.success, "clone should succeed: {output:?}");
+    assert!(
+        checkout.join(".git").is_dir(),
+        "checkout should be at {checkout:?}"
+    );
+
+    // The checkout is no longer empty, so cloning into it again is refused
+    let mut again = git_proxy_mcp::git::command::GitCommand::new(
+        "clone",
+        vec![source_url],
+        Some(dest_dir.path().to_path_buf()),
+    )
+    .expect("clone command should be valid");
+    assert!(
+        again.resolve_clone_target().is_err(),
+        "cloning over a checkout should be refused"
+    );
+
+    // The executor checks the target again, as it may have changed since
+    let result = local_executor().execute(&command).await;
+    assert!(
+        matches!(result, Err(ExecutorError::CloneTarget { .. })),
+        "a target filled after the check should be refused: {result:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_executor_refuses_local_transport_by_default() {
+    if !git_available() {
+        eprintln!("Skipping test: git not available");
+        return;
+    }
+
+    let Some(source_dir) = create_bare_repo() else {
+        eprintln!("Skipping test: failed to create bare repo");
+        return;
+    };
+
+    // Bypasses the transport guard: git itself must refuse the clone
+    let dest_dir = TempDir::new().unwrap();
+    let command = git_proxy_mcp::git::command::GitCommand::new(
+        "clone",
+        vec![source_dir.path().display().to_string(), "copy".to_string()],
+        Some(dest_dir.path().to_path_buf()),
+    )
+    .expect("clone command should be valid");
+
+    let output = GitExecutor::new()
+        .execute(&command)
+        .await
+        .expect("clone should not error");
+
+    assert!(!output.success, "local clone should fail: {output:?}");
+    assert!(
+        output.stderr.contains("transport 'file' not allowed"),
+        "git should refuse the file transport: {}",
+        output.stderr
+    );
+    assert!(!dest_dir.path().join("copy").join(".git").exists());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_executor_neutralises_repository_hooks_and_config() {
+    use std::os::unix::fs::PermissionsExt;
+
+    use git_proxy_mcp::git::executor::ExecutorError;
+    use git_proxy_mcp::git::{LocalConfigGuard, LocalConfigPolicy};
+
+    if !git_available() {
+        eprintln!("Skipping test: git not available");
+        return;
+    }
+
+    let (Some(repo_dir), Some(bare_dir)) = (create_temp_repo(), create_bare_repo()) else {
+        eprintln!("Skipping test: failed to create repos");
+        return;
+    };
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(repo_dir.path())
+            .output()
+            .expect("git should run");
+        assert!(output.status.success(), "git {args:?}: {output:?}");
+    };
+    git(&["commit", "--allow-empty", "-q", "-m", "initial"]);
+    git(&["remote", "add", "origin", bare_dir.path().to_str().unwrap()]);
+    git(&["push", "-q", "origin", "HEAD:refs/heads/main"]);
+    // Make the fetch update a ref, which runs the reference-transaction hook
+    git(&["update-ref", "-d", "refs/remotes/origin/main"]);
+
+    // Both run during a fetch unless they are overridden
+    let markers = TempDir::new().unwrap();
+    let hook_marker = markers.path().join("hook");
+    let fsmonitor_marker = markers.path().join("fsmonitor");
+    let hook = repo_dir.path().join(".git/hooks/reference-transaction");
+    std::fs::write(
+        &hook,
+        format!("#!/bin/sh\ntouch '{}'\n", hook_marker.display()),
+    )
+    .unwrap();
+    std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+    git(&[
+        "config",
+        "core.fsmonitor",
+        &format!("touch '{}'; false", fsmonitor_marker.display()),
+    ]);
+
+    let command = git_proxy_mcp::git::command::GitCommand::new(
+        "fetch",
+        vec!["origin".to_string()],
+        Some(repo_dir.path().to_path_buf()),
+    )
+    .expect("fetch command should be valid");
+
+    let output = local_executor()
+        .execute(&command)
+        .await
+        .expect("fetch should not error");
+    assert!(output.success, "fetch should succeed: {output:?}");
+    assert!(!hook_marker.exists(), "the repository's hook ran");
+    assert!(!fsmonitor_marker.exists(), "the repository's fsmonitor ran");
+
+    let result = local_executor()
+        .with_local_config(LocalConfigGuard::new(LocalConfigPolicy::Reject))
+        .execute(&command)
+        .await;
+    match result {
+        Err(ExecutorError::UnsafeRepositoryConfig { settings }) => {
+            assert_eq!(settings, ["core.fsmonitor", "hook 'reference-transaction'"]);
+        }
+        other => panic!("fetch should be refused: {other:?}"),
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_executor_disables_template_hooks_on_clone() {
+    use std::os::unix::fs::PermissionsExt;
+
+    use git_proxy_mcp::git::GitEnvironment;
+
+    if !git_available() {
+        eprintln!("Skipping test: git not available");
+        return;
+    }
+
+    let (Some(repo_dir), Some(bare_dir)) = (create_temp_repo(), create_bare_repo()) else {
+        eprintln!("Skipping test: failed to create repos");
+        return;
+    };
+    let git = |args: &[&str]| {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(repo_dir.path())
+            .output()
+            .expect("git should run");
+        assert!(output.status.success(), "git {args:?}: {output:?}");
+    };
+    git(&["commit", "--allow-empty", "-q", "-m", "initial"]);
+    git(&[
+        "push",
+        "-q",
+        bare_dir.path().to_str().unwrap(),
+        "HEAD:refs/heads/main",
+    ]);
+
+    // A template's hooks are copied into the new repository and run on checkout
+    let template = TempDir::new().unwrap();
+    let marker = template.path().join("ran");
+    let hook = template.path().join("hooks/post-checkout");
+    std::fs::create_dir(template.path().join("hooks")).unwrap();
+    std::fs::write(&hook, format!("#!/bin/sh\ntouch '{}'\n", marker.display())).unwrap();
+    std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let workspace = TempDir::new().unwrap();
+    let command = git_proxy_mcp::git::command::GitCommand::new(
+        "clone",
+        vec![
+            "--branch".to_string(),
+            "main".to_string(),
+            bare_dir.path().to_str().unwrap().to_string(),
+            "clone".to_string(),
+        ],
+        Some(workspace.path().to_path_buf()),
+    )
+    .expect("clone command should be valid");
+
+    let output = local_executor()
+        .with_environment(
+            GitEnvironment::new()
+                .with_variable("GIT_TEMPLATE_DIR", template.path().to_str().unwrap()),
+        )
+        .execute(&command)
+        .await
+        .expect("clone should not error");
+    assert!(output.success, "clone should succeed: {output:?}");
+    assert!(workspace
+        .path()
+        .join("clone/.git/hooks/post-checkout")
+        .exists());
+    assert!(!marker.exists(), "the template's hook ran");
+}
+
 #[tokio::test]
 async fn test_executor_rejects_nonexistent_working_directory() {
     let executor = GitExecutor::new();
@@ -500,6 +865,28 @@ fn test_rate_limiter_integration() {
     assert_eq!(stats.total_blocked, 1);
 }
 
+#[test]
+fn test_repo_rate_limiter_integration() {
+    use git_proxy_mcp::security::{CommandRateLimiter, RateLimiter, RepoFilter, RepoRateLimiter};
+
+    let limiter = RepoRateLimiter::new(CommandRateLimiter::new(RateLimiter::new(2, 0.0)), 16);
+
+    // Different spellings of the same repository share one bucket
+    let busy_https = RepoFilter::normalise_url("https://github.com/org/busy.git");
+    let busy_ssh = RepoFilter::normalise_url("git@github.com:org/busy.git");
+    assert!(limiter.try_acquire(&busy_https, "fetch").is_ok());
+    assert!(limiter.try_acquire(&busy_ssh, "fetch").is_ok());
+
+    let blocked = limiter
+        .try_acquire(&busy_https, "fetch")
+        .expect_err("Third fetch of the busy repo should be blocked");
+    assert_eq!(blocked.key, "github.com/org/busy");
+
+    // Other repositories are not starved by the runaway one
+    let other = RepoFilter::normalise_url("https://github.com/org/other.git");
+    assert!(limiter.try_acquire(&other, "fetch").is_ok());
+}
+
 // =============================================================================
 // Tool Call Result Tests
 // =============================================================================
@@ -726,7 +1113,7 @@ async fn test_full_pipeline_fetch_in_local_repo() {
         assert!(git_command.is_ok(), "Command should be valid");
 
         // 4. Execute via GitExecutor
-        let executor = GitExecutor::new();
+        let executor = local_executor();
         let result = executor.execute(&git_command.unwrap()).await;
 
         // 5. Verify result
@@ -869,8 +1256,8 @@ async fn test_concurrent_command_execution() {
     )
     .expect("fetch command should be valid");
 
-    let executor1 = GitExecutor::new();
-    let executor2 = GitExecutor::new();
+    let executor1 = local_executor();
+    let executor2 = local_executor();
 
     // Execute fetch commands concurrently using tokio::join!
     let (result1, result2) = tokio::join!(executor1.execute(&cmd1), executor2.execute(&cmd2));
@@ -904,7 +1291,7 @@ async fn test_executor_handles_git_command_failure() {
         return;
     };
 
-    let executor = GitExecutor::new();
+    let executor = local_executor();
 
     // Try to fetch from a non-existent remote
     let command = git_proxy_mcp::git::command::GitCommand::new(